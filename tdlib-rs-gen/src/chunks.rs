@@ -0,0 +1,76 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to group the items of a generated module into chunks, which can be
+//! written to separate files.
+//!
+//! Every file is a private submodule whose items are re-exported by the
+//! module, so that the compiler can split the module in smaller codegen units
+//! than with an `include!` of every file.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The name of the chunk of the items whose name doesn't start with a letter.
+const OTHER_CHUNK: &str = "other";
+
+/// The items of a generated module, grouped by the first letter of their
+/// name if they are going to be written to separate files.
+pub(crate) struct Chunks<'a> {
+    module: &'a str,
+    out_dir: Option<&'a Path>,
+    chunks: BTreeMap<String, Vec<u8>>,
+}
+
+impl<'a> Chunks<'a> {
+    /// Creates the chunks of a module. If `out_dir` is `None`, all the items
+    /// are kept in a single chunk, in the order they are written.
+    pub fn new(module: &'a str, out_dir: Option<&'a Path>) -> Self {
+        Self {
+            module,
+            out_dir,
+            chunks: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the chunk where the item with the given name must be written.
+    pub fn chunk(&mut self, name: &str) -> &mut Vec<u8> {
+        let key = match name.chars().next() {
+            _ if self.out_dir.is_none() => String::new(),
+            Some(c) if c.is_ascii_alphabetic() => c.to_ascii_lowercase().to_string(),
+            _ => OTHER_CHUNK.into(),
+        };
+        self.chunks.entry(key).or_default()
+    }
+
+    /// Writes the chunks in the module, either inline or as submodules in the
+    /// files `<out_dir>/<module>/<chunk>.rs`, whose items are re-exported.
+    pub fn write<W: Write>(self, file: &mut W) -> io::Result<()> {
+        let Some(out_dir) = self.out_dir else {
+            for chunk in self.chunks.values() {
+                file.write_all(chunk)?;
+            }
+            return Ok(());
+        };
+
+        let dir = out_dir.join(self.module);
+        fs::create_dir_all(&dir)?;
+        for (key, chunk) in self.chunks {
+            let mut submodule = b"#[allow(unused_imports)]\nuse super::*;\n\n".to_vec();
+            submodule.extend(chunk);
+            fs::write(dir.join(format!("{key}.rs")), submodule)?;
+            // A chunk is empty if its items are all disabled by a feature
+            writeln!(file, "    mod {key};")?;
+            writeln!(file, "    #[allow(unused_imports)]")?;
+            writeln!(file, "    pub use {key}::*;")?;
+        }
+        Ok(())
+    }
+}
@@ -10,10 +10,13 @@
 
 //! Code to generate Rust's `enum`'s from TL definitions.
 
+use crate::GenOptions;
+use crate::chunks::Chunks;
 use crate::ignore_type;
 use crate::metadata::Metadata;
 use crate::rustifier;
 use std::io::{self, Write};
+use std::path::Path;
 use tdlib_rs_parser::tl::{Category, Definition, Type};
 
 /// Writes an enumeration listing all types such as the following rust code:
@@ -27,24 +30,31 @@ fn write_enum<W: Write>(
     file: &mut W,
     ty: &Type,
     metadata: &Metadata,
-    gen_bots_only_api: bool,
+    options: &GenOptions,
 ) -> io::Result<()> {
-    writeln!(
-        file,
-        "    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]",
-    )?;
-    writeln!(file, "    #[serde(tag = \"@type\")]")?;
-    writeln!(file, "    pub enum {} {{", rustifier::types::type_name(ty))?;
-    for d in metadata.defs_with_type(ty) {
-        if rustifier::definitions::is_for_bots_only(d) && !gen_bots_only_api {
-            continue;
-        }
-
+    if let Some(class) = metadata.class(ty).filter(|_| options.docs) {
         writeln!(
             file,
             "{}",
-            rustifier::definitions::description(d, "        ")
+            rustifier::classes::description(class, "    ", metadata.doc_links())
         )?;
+    }
+    write!(file, "    #[derive(Clone, Debug, ")?;
+    for derive in options.item_derives(false, metadata.type_contains_float(ty)) {
+        write!(file, "{derive}, ")?;
+    }
+    writeln!(file, "PartialEq, Deserialize, Serialize)]")?;
+    writeln!(file, "    #[serde(tag = \"@type\")]")?;
+    writeln!(file, "    pub enum {} {{", rustifier::types::type_name(ty))?;
+    for d in variant_defs(ty, metadata, options) {
+        if options.docs {
+            writeln!(
+                file,
+                "{}",
+                rustifier::definitions::description(d, "        ", metadata.doc_links())
+            )?;
+        }
+        write!(file, "{}", variant_attrs(d, options, "        "))?;
         writeln!(
             file,
             "        #[serde(rename(serialize = \"{0}\", deserialize = \"{0}\"))]",
@@ -63,7 +73,11 @@ fn write_enum<W: Write>(
         if metadata.is_recursive_def(d) {
             write!(file, "Box<")?;
         }
-        write!(file, "{}", rustifier::definitions::qual_name(d))?;
+        write!(
+            file,
+            "{}",
+            rustifier::definitions::qual_name(d, &options.root_path)
+        )?;
         if metadata.is_recursive_def(d) {
             write!(file, ">")?;
         }
@@ -71,15 +85,220 @@ fn write_enum<W: Write>(
         writeln!(file, "),")?;
     }
     writeln!(file, "    }}")?;
+
+    write_accessors(file, ty, metadata, options)?;
+    write_conversions(file, ty, metadata, options)
+}
+
+/// Returns the definitions of the variants of an enumeration.
+fn variant_defs<'a>(
+    ty: &'a Type,
+    metadata: &'a Metadata<'a>,
+    options: &GenOptions,
+) -> Vec<&'a Definition> {
+    metadata
+        .defs_with_type(ty)
+        .iter()
+        .copied()
+        .filter(|d| options.generates(rustifier::definitions::is_for_bots_only(d)))
+        .collect()
+}
+
+/// Returns the attributes of the items generated for a variant, which are
+/// behind a `cfg` if it's only used by bots.
+fn variant_attrs(def: &Definition, options: &GenOptions, indent: &str) -> String {
+    options.bots_only_attrs(rustifier::definitions::is_for_bots_only(def), indent, true)
+}
+
+/// Returns the attribute of the wildcard arm of a `match` on an enumeration,
+/// which is unreachable if only one of its variants is not behind a `cfg`.
+fn wildcard_attr(defs: &[&Definition], options: &GenOptions) -> &'static str {
+    let unconditional_defs = defs
+        .iter()
+        .filter(|d| variant_attrs(d, options, "").is_empty())
+        .count();
+    if unconditional_defs < 2 {
+        "#[allow(unreachable_patterns)] "
+    } else {
+        ""
+    }
+}
+
+/// Writes the methods to inspect the variants of an enumeration such as the
+/// following rust code:
+///
+/// ```ignore
+/// impl Name {
+///     pub fn is_variant(&self) -> bool { .. }
+///     pub fn as_variant(&self) -> Option<&crate::types::Name> { .. }
+///     pub fn as_variant_mut(&mut self) -> Option<&mut crate::types::Name> { .. }
+///     pub fn into_variant(self) -> Option<crate::types::Name> { .. }
+/// }
+/// ```
+fn write_accessors<W: Write>(
+    file: &mut W,
+    ty: &Type,
+    metadata: &Metadata,
+    options: &GenOptions,
+) -> io::Result<()> {
+    let defs = variant_defs(ty, metadata, options);
+    if defs.is_empty() {
+        return Ok(());
+    }
+    let enum_name = rustifier::types::type_name(ty);
+    let has_other_variants = defs.len() > 1;
+    let wildcard_attr = wildcard_attr(&defs, options);
+
+    writeln!(file, "    impl {enum_name} {{")?;
+    for d in &defs {
+        let variant_name = rustifier::definitions::variant_name(d);
+        let method_name = rustifier::definitions::variant_method_name(d);
+        let pattern = if d.params.is_empty() {
+            format!("{enum_name}::{variant_name}")
+        } else {
+            format!("{enum_name}::{variant_name}(..)")
+        };
+
+        let attrs = variant_attrs(d, options, "        ");
+
+        writeln!(
+            file,
+            "        /// Returns `true` if the value is a `{variant_name}` variant"
+        )?;
+        write!(file, "{attrs}")?;
+        writeln!(file, "        pub fn is_{method_name}(&self) -> bool {{")?;
+        writeln!(file, "            match self {{")?;
+        writeln!(file, "                {pattern} => true,")?;
+        if has_other_variants {
+            writeln!(file, "                {wildcard_attr}_ => false,")?;
+        }
+        writeln!(file, "            }}")?;
+        writeln!(file, "        }}")?;
+
+        if d.params.is_empty() {
+            continue;
+        }
+
+        let qual_name = rustifier::definitions::qual_name(d, &options.root_path);
+        let (as_ref, as_mut, into) = if metadata.is_recursive_def(d) {
+            ("value.as_ref()", "value.as_mut()", "*value")
+        } else {
+            ("value", "value", "value")
+        };
+        let accessors = [
+            ("as_", "", "&self", "&"),
+            ("as_", "_mut", "&mut self", "&mut "),
+            ("into_", "", "self", ""),
+        ];
+        for ((prefix, suffix, receiver, reference), value) in
+            accessors.into_iter().zip([as_ref, as_mut, into])
+        {
+            writeln!(
+                file,
+                "        /// Returns the content of the value if it's a `{variant_name}` variant"
+            )?;
+            write!(file, "{attrs}")?;
+            writeln!(
+                file,
+                "        pub fn {prefix}{method_name}{suffix}({receiver}) -> Option<{reference}{qual_name}> {{"
+            )?;
+            writeln!(file, "            match self {{")?;
+            writeln!(
+                file,
+                "                {enum_name}::{variant_name}(value) => Some({value}),"
+            )?;
+            if has_other_variants {
+                writeln!(file, "                {wildcard_attr}_ => None,")?;
+            }
+            writeln!(file, "            }}")?;
+            writeln!(file, "        }}")?;
+        }
+    }
+    writeln!(file, "    }}")?;
+    Ok(())
+}
+
+/// Writes the conversions between an enumeration and the types of its
+/// variants such as the following rust code:
+///
+/// ```ignore
+/// impl From<crate::types::Name> for Name {
+///     fn from(value: crate::types::Name) -> Self {
+///         Name::Variant(value)
+///     }
+/// }
+/// impl TryFrom<Name> for crate::types::Name {
+///     type Error = Name;
+///     fn try_from(value: Name) -> Result<Self, Self::Error> {
+///         match value {
+///             Name::Variant(value) => Ok(value),
+///             value => Err(value),
+///         }
+///     }
+/// }
+/// ```
+fn write_conversions<W: Write>(
+    file: &mut W,
+    ty: &Type,
+    metadata: &Metadata,
+    options: &GenOptions,
+) -> io::Result<()> {
+    let defs = variant_defs(ty, metadata, options);
+    let enum_name = rustifier::types::type_name(ty);
+    let wildcard_attr = wildcard_attr(&defs, options);
+
+    for d in defs.iter().filter(|d| !d.params.is_empty()) {
+        let attrs = variant_attrs(d, options, "    ");
+        let qual_name = rustifier::definitions::qual_name(d, &options.root_path);
+        let variant_name = rustifier::definitions::variant_name(d);
+        let (boxed, unboxed) = if metadata.is_recursive_def(d) {
+            ("Box::new(value)", "*value")
+        } else {
+            ("value", "value")
+        };
+
+        write!(file, "{attrs}")?;
+        writeln!(file, "    impl From<{qual_name}> for {enum_name} {{")?;
+        writeln!(file, "        fn from(value: {qual_name}) -> Self {{")?;
+        writeln!(file, "            {enum_name}::{variant_name}({boxed})")?;
+        writeln!(file, "        }}")?;
+        writeln!(file, "    }}")?;
+
+        write!(file, "{attrs}")?;
+        writeln!(file, "    impl TryFrom<{enum_name}> for {qual_name} {{")?;
+        writeln!(file, "        type Error = {enum_name};")?;
+        writeln!(
+            file,
+            "        fn try_from(value: {enum_name}) -> Result<Self, Self::Error> {{"
+        )?;
+        if defs.len() == 1 {
+            writeln!(
+                file,
+                "            let {enum_name}::{variant_name}(value) = value;"
+            )?;
+            writeln!(file, "            Ok({unboxed})")?;
+        } else {
+            writeln!(file, "            match value {{")?;
+            writeln!(
+                file,
+                "                {enum_name}::{variant_name}(value) => Ok({unboxed}),"
+            )?;
+            writeln!(file, "                {wildcard_attr}value => Err(value),")?;
+            writeln!(file, "            }}")?;
+        }
+        writeln!(file, "        }}")?;
+        writeln!(file, "    }}")?;
+    }
     Ok(())
 }
 
 /// Write the entire module dedicated to enums.
 pub(crate) fn write_enums_mod<W: Write>(
-    mut file: &mut W,
+    file: &mut W,
     definitions: &[Definition],
     metadata: &Metadata,
-    gen_bots_only_api: bool,
+    options: &GenOptions,
+    out_dir: Option<&Path>,
 ) -> io::Result<()> {
     // Begin outermost mod
     writeln!(file, "#[allow(clippy::all)]")?;
@@ -93,10 +312,185 @@ pub(crate) fn write_enums_mod<W: Write>(
         .collect();
     enums.dedup();
 
+    let mut chunks = Chunks::new("enums", out_dir);
     for ty in enums {
-        write_enum(&mut file, ty, metadata, gen_bots_only_api)?;
+        let chunk = chunks.chunk(&rustifier::types::type_name(ty));
+        write_enum(chunk, ty, metadata, options)?;
     }
+    chunks.write(file)?;
 
     // End outermost mod
     writeln!(file, "}}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the definitions, marking the ones whose name ends with `Bot` as
+    /// only used by bots.
+    fn definitions(definitions: &[&str]) -> Vec<Definition> {
+        definitions
+            .iter()
+            .map(|d| {
+                let mut d = d.parse::<Definition>().unwrap();
+                if d.name.ends_with("Bot") {
+                    d.description = "A definition; for bots only".into();
+                }
+                d
+            })
+            .collect()
+    }
+
+    /// Returns the code written by `write` for the type of the first
+    /// definition.
+    fn generate(
+        definitions: &[Definition],
+        write: fn(&mut Vec<u8>, &Type, &Metadata, &GenOptions) -> io::Result<()>,
+    ) -> String {
+        let options = GenOptions::default();
+        let metadata = Metadata::new(definitions, &[], &options);
+        let mut file = Vec::new();
+        write(&mut file, &definitions[0].ty, &metadata, &options).unwrap();
+        String::from_utf8(file).unwrap()
+    }
+
+    #[test]
+    fn check_single_variant_conversions() {
+        let definitions = definitions(&["textEntity offset:int32 = TextEntity"]);
+        assert_eq!(
+            generate(&definitions, write_conversions),
+            "    impl From<crate::types::TextEntity> for TextEntity {
+        fn from(value: crate::types::TextEntity) -> Self {
+            TextEntity::TextEntity(value)
+        }
+    }
+    impl TryFrom<TextEntity> for crate::types::TextEntity {
+        type Error = TextEntity;
+        fn try_from(value: TextEntity) -> Result<Self, Self::Error> {
+            let TextEntity::TextEntity(value) = value;
+            Ok(value)
+        }
+    }
+"
+        );
+    }
+
+    #[test]
+    fn check_boxed_conversions() {
+        let definitions = definitions(&[
+            "pageBlockCover cover:PageBlock = PageBlock",
+            "pageBlockTitle title:string = PageBlock",
+            "pageBlockDivider = PageBlock",
+        ]);
+        let code = generate(&definitions, write_conversions);
+        assert!(code.contains("            PageBlock::Cover(Box::new(value))\n"));
+        assert!(code.contains("                PageBlock::Cover(value) => Ok(*value),\n"));
+        assert!(code.contains("            PageBlock::Title(value)\n"));
+        assert!(code.contains("                PageBlock::Title(value) => Ok(value),\n"));
+        assert!(code.contains("                value => Err(value),\n"));
+        // Variants without data have no type to convert
+        assert!(!code.contains("Divider"));
+    }
+
+    #[test]
+    fn check_cfg_gated_conversions() {
+        let definitions = definitions(&[
+            "inputCredentials data:string = InputCredentials",
+            "inputCredentialsBot token:string = InputCredentials",
+        ]);
+        let code = generate(&definitions, write_conversions);
+        assert!(code.contains(
+            "    #[cfg(feature = \"bots-only-api\")]
+    #[cfg_attr(docsrs, doc(cfg(feature = \"bots-only-api\")))]
+    impl From<crate::types::InputCredentialsBot> for InputCredentials {
+"
+        ));
+        // The only other variant may be disabled, so the wildcard arm may be
+        // unreachable
+        assert_eq!(
+            code.matches("                #[allow(unreachable_patterns)] value => Err(value),\n")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn check_single_variant_accessors() {
+        let definitions = definitions(&["textEntity offset:int32 = TextEntity"]);
+        let code = generate(&definitions, write_accessors);
+        assert!(code.contains(
+            "        pub fn is_text_entity(&self) -> bool {
+            match self {
+                TextEntity::TextEntity(..) => true,
+            }
+        }
+"
+        ));
+        assert!(code.contains(
+            "        pub fn into_text_entity(self) -> Option<crate::types::TextEntity> {
+            match self {
+                TextEntity::TextEntity(value) => Some(value),
+            }
+        }
+"
+        ));
+        // A single variant needs no wildcard arm
+        assert!(!code.contains("_ =>"));
+    }
+
+    #[test]
+    fn check_boxed_accessors() {
+        let definitions = definitions(&[
+            "pageBlockCover cover:PageBlock = PageBlock",
+            "pageBlockTitle title:string = PageBlock",
+            "pageBlockDivider = PageBlock",
+        ]);
+        let code = generate(&definitions, write_accessors);
+        assert!(code.contains(
+            "        pub fn as_cover(&self) -> Option<&crate::types::PageBlockCover> {
+            match self {
+                PageBlock::Cover(value) => Some(value.as_ref()),
+                _ => None,
+            }
+        }
+"
+        ));
+        assert!(
+            code.contains("                PageBlock::Cover(value) => Some(value.as_mut()),\n")
+        );
+        assert!(code.contains("                PageBlock::Cover(value) => Some(*value),\n"));
+        assert!(code.contains("                PageBlock::Title(value) => Some(value),\n"));
+        // Variants without data only have an `is_` method
+        assert!(code.contains("                PageBlock::Divider => true,\n"));
+        assert!(!code.contains("fn as_divider"));
+        assert!(!code.contains("fn into_divider"));
+    }
+
+    #[test]
+    fn check_cfg_gated_accessors() {
+        let definitions = definitions(&[
+            "inputCredentials data:string = InputCredentials",
+            "inputCredentialsBot token:string = InputCredentials",
+        ]);
+        let code = generate(&definitions, write_accessors);
+        assert!(code.contains(
+            "        #[cfg(feature = \"bots-only-api\")]
+        #[cfg_attr(docsrs, doc(cfg(feature = \"bots-only-api\")))]
+        pub fn as_bot_mut(&mut self) -> Option<&mut crate::types::InputCredentialsBot> {
+"
+        ));
+        // The only other variant may be disabled, so the wildcard arms may be
+        // unreachable
+        assert_eq!(
+            code.matches("#[allow(unreachable_patterns)] _ => false,")
+                .count(),
+            2
+        );
+        assert_eq!(
+            code.matches("#[allow(unreachable_patterns)] _ => None,")
+                .count(),
+            6
+        );
+    }
+}
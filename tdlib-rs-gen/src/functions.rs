@@ -10,11 +10,50 @@
 
 //! Code to generate Rust's `fn`'s from TL definitions.
 
+use crate::chunks::Chunks;
 use crate::metadata::Metadata;
 use crate::rustifier;
+use crate::{BotsOnlyApi, GenOptions};
 use std::io::{self, Write};
+use std::path::Path;
 use tdlib_rs_parser::tl::{Category, Definition};
 
+/// Writes the documentation of the function corresponding to the definition,
+/// including its arguments.
+fn write_function_doc<W: Write>(
+    file: &mut W,
+    def: &Definition,
+    metadata: &Metadata,
+    options: &GenOptions,
+) -> io::Result<()> {
+    writeln!(
+        file,
+        "{}",
+        rustifier::definitions::description(def, "    ", metadata.doc_links())
+    )?;
+    writeln!(file, "    /// # Arguments")?;
+    for param in def.params.iter() {
+        if !options.generates(rustifier::parameters::is_for_bots_only(param)) {
+            continue;
+        }
+
+        let description = rustifier::parameters::description(param, "    ", metadata.doc_links());
+        writeln!(
+            file,
+            "{}",
+            description.replacen(
+                "/// ",
+                &format!("/// * `{}` - ", rustifier::parameters::attr_name(param)),
+                1
+            )
+        )?;
+    }
+    writeln!(
+        file,
+        "    /// * `client_id` - The client id to send the request to"
+    )
+}
+
 /// Defines the `function` corresponding to the definition:
 ///
 /// ```ignore
@@ -22,55 +61,64 @@ use tdlib_rs_parser::tl::{Category, Definition};
 ///
 /// }
 /// ```
+///
+/// If `blocking` is set, a synchronous `pub fn` is written instead.
 fn write_function<W: Write>(
     file: &mut W,
     def: &Definition,
-    _metadata: &Metadata,
-    gen_bots_only_api: bool,
+    metadata: &Metadata,
+    options: &GenOptions,
+    blocking: bool,
 ) -> io::Result<()> {
-    if rustifier::definitions::is_for_bots_only(def) && !gen_bots_only_api {
+    let bots_only = rustifier::definitions::is_for_bots_only(def);
+    if !options.generates(bots_only) {
         return Ok(());
     }
 
-    // Documentation
-    writeln!(file, "{}", rustifier::definitions::description(def, "    "))?;
-    writeln!(file, "    /// # Arguments")?;
-    for param in def.params.iter() {
-        if rustifier::parameters::is_for_bots_only(param) && !gen_bots_only_api {
-            continue;
-        }
+    let params = def
+        .params
+        .iter()
+        .filter(|p| options.generates(rustifier::parameters::is_for_bots_only(p)))
+        .collect::<Vec<_>>();
+    // The parameters only used by bots are added to the request separately
+    // if they are behind a `cfg`, since `json!` doesn't support attributes
+    let (cfg_params, params): (Vec<_>, Vec<_>) = params.into_iter().partition(|p| {
+        rustifier::parameters::is_for_bots_only(p)
+            && matches!(options.bots_only_api, BotsOnlyApi::Feature(_))
+    });
 
-        writeln!(
-            file,
-            "    /// * `{}` - {}",
-            rustifier::parameters::attr_name(param),
-            param.description.replace('\n', "\n    /// ")
-        )?;
+    // Documentation
+    if options.docs {
+        write_function_doc(file, def, metadata, options)?;
     }
-    writeln!(
-        file,
-        "    /// * `client_id` - The client id to send the request to"
-    )?;
 
     // Function
+    write!(file, "{}", options.bots_only_attrs(bots_only, "    ", true))?;
     writeln!(file, "    #[allow(clippy::too_many_arguments)]")?;
     write!(
         file,
-        "    pub async fn {}(",
+        "    pub {}fn {}(",
+        if blocking { "" } else { "async " },
         rustifier::definitions::function_name(def)
     )?;
     for param in def.params.iter() {
-        if rustifier::parameters::is_for_bots_only(param) && !gen_bots_only_api {
+        let bots_only = rustifier::parameters::is_for_bots_only(param);
+        if !options.generates(bots_only) {
             continue;
         }
 
+        write!(file, "{}", options.bots_only_attrs(bots_only, "", false))?;
         write!(file, "{}: ", rustifier::parameters::attr_name(param))?;
 
         let is_optional = rustifier::parameters::is_optional(param);
         if is_optional {
             write!(file, "Option<")?;
         }
-        write!(file, "{}", rustifier::parameters::qual_name(param))?;
+        write!(
+            file,
+            "{}",
+            rustifier::parameters::qual_name(param, &options.root_path)
+        )?;
         if is_optional {
             write!(file, ">")?;
         }
@@ -80,18 +128,20 @@ fn write_function<W: Write>(
 
     writeln!(
         file,
-        "client_id: i32) -> Result<{}, crate::types::Error> {{",
-        rustifier::types::qual_name(&def.ty, false)
+        "client_id: i32) -> Result<{}, {}::types::Error> {{",
+        rustifier::types::qual_name(&def.ty, false, &options.root_path),
+        options.root_path
     )?;
 
     // Compose request
-    writeln!(file, "        let request = json!({{")?;
+    if cfg_params.is_empty() {
+        writeln!(file, "        let request = json!({{")?;
+    } else {
+        writeln!(file, "        #[allow(unused_mut)]")?;
+        writeln!(file, "        let mut request = json!({{")?;
+    }
     writeln!(file, "            \"@type\": \"{}\",", def.name)?;
-    for param in def.params.iter() {
-        if rustifier::parameters::is_for_bots_only(param) && !gen_bots_only_api {
-            continue;
-        }
-
+    for param in params {
         writeln!(
             file,
             "            \"{0}\": {1},",
@@ -100,12 +150,30 @@ fn write_function<W: Write>(
         )?;
     }
     writeln!(file, "        }});")?;
+    for param in cfg_params {
+        write!(file, "{}", options.bots_only_attrs(true, "        ", false))?;
+        writeln!(file, "        {{")?;
+        writeln!(
+            file,
+            "            request[\"{0}\"] = json!({1});",
+            param.name,
+            rustifier::parameters::attr_name(param),
+        )?;
+        writeln!(file, "        }}")?;
+    }
 
     // Send request
-    writeln!(
-        file,
-        "        let response = send_request(client_id, request).await;"
-    )?;
+    if blocking {
+        writeln!(
+            file,
+            "        let response = send_request_blocking(client_id, request);"
+        )?;
+    } else {
+        writeln!(
+            file,
+            "        let response = send_request(client_id, request).await;"
+        )?;
+    }
     writeln!(file, "        if response[\"@type\"] == \"error\" {{")?;
     writeln!(
         file,
@@ -131,32 +199,70 @@ fn write_definition<W: Write>(
     file: &mut W,
     def: &Definition,
     metadata: &Metadata,
-    gen_bots_only_api: bool,
+    options: &GenOptions,
+    blocking: bool,
 ) -> io::Result<()> {
-    write_function(file, def, metadata, gen_bots_only_api)?;
+    write_function(file, def, metadata, options, blocking)?;
     Ok(())
 }
 
 /// Write the entire module dedicated to functions.
 pub(crate) fn write_functions_mod<W: Write>(
-    mut file: &mut W,
+    file: &mut W,
     definitions: &[Definition],
     metadata: &Metadata,
-    gen_bots_only_api: bool,
+    options: &GenOptions,
+    out_dir: Option<&Path>,
 ) -> io::Result<()> {
     // Begin outermost mod
     writeln!(file, "#[allow(clippy::all)]")?;
     writeln!(file, "pub mod functions {{")?;
     writeln!(file, "    use serde_json::json;")?;
-    writeln!(file, "    use crate::send_request;")?;
+    writeln!(file, "    use {}::send_request;", options.root_path)?;
+
+    let functions = definitions
+        .iter()
+        .filter(|d| d.category == Category::Functions);
+
+    let mut chunks = Chunks::new("functions", out_dir);
+    for definition in functions {
+        let chunk = chunks.chunk(&rustifier::definitions::function_name(definition));
+        write_definition(chunk, definition, metadata, options, false)?;
+    }
+    chunks.write(file)?;
+
+    // End outermost mod
+    writeln!(file, "}}")
+}
+
+/// Write the module dedicated to the synchronous version of the functions.
+pub(crate) fn write_blocking_functions_mod<W: Write>(
+    file: &mut W,
+    definitions: &[Definition],
+    metadata: &Metadata,
+    options: &GenOptions,
+    out_dir: Option<&Path>,
+) -> io::Result<()> {
+    // Begin outermost mod
+    writeln!(file, "#[allow(clippy::all)]")?;
+    writeln!(file, "pub mod blocking {{")?;
+    writeln!(file, "    use serde_json::json;")?;
+    writeln!(
+        file,
+        "    use {}::send_request_blocking;",
+        options.root_path
+    )?;
 
     let functions = definitions
         .iter()
         .filter(|d| d.category == Category::Functions);
 
+    let mut chunks = Chunks::new("blocking", out_dir);
     for definition in functions {
-        write_definition(&mut file, definition, metadata, gen_bots_only_api)?;
+        let chunk = chunks.chunk(&rustifier::definitions::function_name(definition));
+        write_definition(chunk, definition, metadata, options, true)?;
     }
+    chunks.write(file)?;
 
     // End outermost mod
     writeln!(file, "}}")
@@ -0,0 +1,162 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to generate the `UpdateHandler` trait from the `Update` definitions.
+
+use crate::GenOptions;
+use crate::metadata::Metadata;
+use crate::rustifier;
+use std::io::{self, Write};
+use tdlib_rs_parser::tl::{Category, Definition};
+
+/// The name of the type whose variants are handled.
+const UPDATE_TYPE: &str = "Update";
+
+/// Writes the method of the trait handling the definition:
+///
+/// ```ignore
+/// fn on_name(&mut self, client_id: i32, update: crate::types::UpdateName) -> impl Future<Output = ()> + Send {
+///     async {}
+/// }
+/// ```
+fn write_method<W: Write>(
+    file: &mut W,
+    def: &Definition,
+    metadata: &Metadata,
+    options: &GenOptions,
+) -> io::Result<()> {
+    let method_name = rustifier::definitions::variant_method_name(def);
+
+    if options.docs {
+        writeln!(
+            file,
+            "{}",
+            rustifier::definitions::description(def, "        ", metadata.doc_links())
+        )?;
+    }
+    let bots_only = rustifier::definitions::is_for_bots_only(def);
+    write!(
+        file,
+        "{}",
+        options.bots_only_attrs(bots_only, "        ", true)
+    )?;
+    if def.params.is_empty() {
+        writeln!(
+            file,
+            "        fn on_{method_name}(&mut self, client_id: i32) -> impl Future<Output = ()> + Send {{"
+        )?;
+        writeln!(file, "            let _ = client_id;")?;
+    } else {
+        writeln!(
+            file,
+            "        fn on_{}(&mut self, client_id: i32, update: {}) -> impl Future<Output = ()> + Send {{",
+            method_name,
+            rustifier::definitions::qual_name(def, &options.root_path)
+        )?;
+        writeln!(file, "            let _ = (client_id, update);")?;
+    }
+    writeln!(file, "            async {{}}")?;
+    writeln!(file, "        }}")
+}
+
+/// Writes the arm of the `dispatch` function calling the method handling the
+/// definition.
+fn write_dispatch_arm<W: Write>(
+    file: &mut W,
+    def: &Definition,
+    metadata: &Metadata,
+    options: &GenOptions,
+) -> io::Result<()> {
+    let method_name = rustifier::definitions::variant_method_name(def);
+    let variant_name = rustifier::definitions::variant_name(def);
+    let bots_only = rustifier::definitions::is_for_bots_only(def);
+    write!(
+        file,
+        "{}",
+        options.bots_only_attrs(bots_only, "            ", false)
+    )?;
+
+    if def.params.is_empty() {
+        writeln!(
+            file,
+            "            Update::{variant_name} => handler.on_{method_name}(client_id).await,"
+        )
+    } else {
+        let update = if metadata.is_recursive_def(def) {
+            "*update"
+        } else {
+            "update"
+        };
+        writeln!(
+            file,
+            "            Update::{variant_name}(update) => handler.on_{method_name}(client_id, {update}).await,"
+        )
+    }
+}
+
+/// Write the entire module dedicated to the update handler.
+pub(crate) fn write_handler_mod<W: Write>(
+    mut file: &mut W,
+    definitions: &[Definition],
+    metadata: &Metadata,
+    options: &GenOptions,
+) -> io::Result<()> {
+    let updates: Vec<&Definition> = definitions
+        .iter()
+        .filter(|d| d.category == Category::Types && d.ty.name == UPDATE_TYPE)
+        .filter(|d| options.generates(rustifier::definitions::is_for_bots_only(d)))
+        .collect();
+    if updates.is_empty() {
+        return Ok(());
+    }
+
+    // Begin outermost mod
+    writeln!(file, "#[allow(clippy::all)]")?;
+    writeln!(file, "pub mod update_handler {{")?;
+    writeln!(file, "    use {}::Update;", options.module_path("enums"))?;
+    writeln!(file, "    use std::future::Future;")?;
+
+    // Trait
+    writeln!(
+        file,
+        "    /// A handler of updates, with a method for every kind of update. All the"
+    )?;
+    writeln!(
+        file,
+        "    /// methods do nothing by default, so only the handled updates need to be"
+    )?;
+    writeln!(
+        file,
+        "    /// implemented. The methods can be implemented with `async fn`, as long as"
+    )?;
+    writeln!(file, "    /// the returned futures are `Send`.")?;
+    writeln!(file, "    pub trait UpdateHandler {{")?;
+    for def in &updates {
+        write_method(&mut file, def, metadata, options)?;
+    }
+    writeln!(file, "    }}")?;
+
+    // Dispatch function
+    writeln!(
+        file,
+        "    /// Call the method of the handler corresponding to the update."
+    )?;
+    writeln!(
+        file,
+        "    pub async fn dispatch<H: UpdateHandler>(handler: &mut H, update: Update, client_id: i32) {{"
+    )?;
+    writeln!(file, "        match update {{")?;
+    for def in &updates {
+        write_dispatch_arm(&mut file, def, metadata, options)?;
+    }
+    writeln!(file, "        }}")?;
+    writeln!(file, "    }}")?;
+
+    // End outermost mod
+    writeln!(file, "}}")
+}
@@ -10,14 +10,22 @@
 
 //! This module gathers all the code generation submodules and coordinates
 //! them, feeding them the right data.
+mod chunks;
 mod enums;
 mod functions;
+mod handler;
 mod metadata;
+mod options;
 mod rustifier;
 mod types;
 
-use std::io::{self, Write};
-use tdlib_rs_parser::tl::{Definition, Type};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use tdlib_rs_parser::tl::{Class, Definition, Type};
+
+pub use options::{BotsOnlyApi, GenOptions};
 
 /// Don't generate types for definitions of this type,
 /// since they are "core" types and treated differently.
@@ -27,10 +35,51 @@ fn ignore_type(ty: &Type) -> bool {
     SPECIAL_CASED_TYPES.iter().any(|&x| x == ty.name)
 }
 
+/// Keeps only the definitions listed in the `allowlist` and the ones they
+/// require, such as the types of their parameters, transitively. The variants
+/// of `Update` are only kept if listed. Returns the names in the `allowlist`
+/// that are not defined.
+pub fn retain_allowlisted(definitions: &mut Vec<Definition>, allowlist: &[&str]) -> Vec<String> {
+    let options = GenOptions::default().bots_only_api(BotsOnlyApi::Include);
+    let metadata = metadata::Metadata::new(definitions, &[], &options);
+    let (required, unknown) = metadata.required_defs(definitions, allowlist);
+    let required = required.into_iter().cloned().collect::<HashSet<_>>();
+    definitions.retain(|d| required.contains(&d.name));
+    unknown
+}
+
+/// Generates the Rust code of the definitions in a single file.
 pub fn generate_rust_code(
     file: &mut impl Write,
     definitions: &[Definition],
-    gen_bots_only_api: bool,
+    classes: &[Class],
+    options: &GenOptions,
+) -> io::Result<()> {
+    write_rust_code(file, definitions, classes, options, None)
+}
+
+/// Generates the Rust code of the definitions in a directory. The root module
+/// is written to `mod.rs` and re-exports the items of the `types`, `enums` and
+/// `functions` modules from submodules in smaller files, grouped by the first
+/// letter of their name, such as `types/a.rs`.
+pub fn generate_rust_code_split(
+    dir: &Path,
+    definitions: &[Definition],
+    classes: &[Class],
+    options: &GenOptions,
+) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut file = BufWriter::new(File::create(dir.join("mod.rs"))?);
+    write_rust_code(&mut file, definitions, classes, options, Some(dir))?;
+    file.flush()
+}
+
+fn write_rust_code(
+    file: &mut impl Write,
+    definitions: &[Definition],
+    classes: &[Class],
+    options: &GenOptions,
+    out_dir: Option<&Path>,
 ) -> io::Result<()> {
     write!(
         file,
@@ -47,10 +96,44 @@ pub fn generate_rust_code(
          "
     )?;
 
-    let metadata = metadata::Metadata::new(definitions);
-    types::write_types_mod(file, definitions, &metadata, gen_bots_only_api)?;
-    enums::write_enums_mod(file, definitions, &metadata, gen_bots_only_api)?;
-    functions::write_functions_mod(file, definitions, &metadata, gen_bots_only_api)?;
+    let metadata = metadata::Metadata::new(definitions, classes, options);
+    types::write_types_mod(file, definitions, &metadata, options, out_dir)?;
+    enums::write_enums_mod(file, definitions, &metadata, options, out_dir)?;
+    functions::write_functions_mod(file, definitions, &metadata, options, out_dir)?;
+    handler::write_handler_mod(file, definitions, &metadata, options)?;
+    if options.blocking_api {
+        functions::write_blocking_functions_mod(file, definitions, &metadata, options, out_dir)?;
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tdlib_rs_parser::tl::Category;
+
+    #[test]
+    fn check_retain_allowlisted() {
+        let mut definitions = [
+            "error code:int32 message:string = Error",
+            "chat id:int53 title:string = Chat",
+            "user id:int53 = User",
+            "updateNewChat chat:chat = Update",
+            "updateUser user:user = Update",
+            "getChat chat_id:int53 = Chat",
+        ]
+        .map(|d| d.parse::<Definition>().unwrap())
+        .into_iter()
+        .collect::<Vec<_>>();
+        definitions[5].category = Category::Functions;
+
+        let unknown = retain_allowlisted(&mut definitions, &["getChat", "updateChat"]);
+        let names = definitions
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["error", "chat", "getChat"]);
+        assert_eq!(unknown, ["updateChat"]);
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Command line interface to generate the Rust code of a `.tl` file, so that
+//! it can be checked in instead of being generated by a build script.
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use tdlib_rs_gen::{
+    BotsOnlyApi, GenOptions, generate_rust_code, generate_rust_code_split, retain_allowlisted,
+};
+use tdlib_rs_parser::{parse_tl_classes, parse_tl_file};
+
+const USAGE: &str = "\
+Generate the Rust code of the TDLib API from a .tl file.
+
+Usage: tdlib-rs-gen [OPTIONS] <TL_FILE> <OUT_DIR>
+
+Options:
+    --bots-only-api <MODE>
+                         How the definitions only used by Telegram bots are
+                         generated: `include`, `exclude` or `feature:<NAME>`
+                         to put them behind a feature [default: include]
+    --blocking           Generate the `blocking` module
+    --no-docs            Don't generate the documentation of the items
+    --root-path <PATH>   The path of the module containing the generated
+                         modules [default: crate]
+    --derive <TRAITS>    The traits derived by the types and the enums in
+                         addition to the required ones, separated by commas
+                         [default: Default]
+    --allowlist <NAMES>  Generate only the listed definitions, separated by
+                         commas, and the types they require
+    --single-file        Write all the code to `<OUT_DIR>/mod.rs`
+    --no-format          Don't format the generated code with rustfmt
+    -h, --help           Print this help
+";
+
+/// The options given on the command line.
+#[derive(Debug, Default)]
+struct Args {
+    tl_file: PathBuf,
+    out_dir: PathBuf,
+    options: GenOptions,
+    allowlist: Option<String>,
+    single_file: bool,
+    no_format: bool,
+}
+
+impl Args {
+    /// Parse the arguments, returning `None` if the help must be printed.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut result = Self::default();
+        // The generated code is usually checked in by a crate without the
+        // `bots-only-api` feature of tdlib-rs
+        result.options.bots_only_api = BotsOnlyApi::Include;
+        let mut paths = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--bots-only-api" => {
+                    let mode = args.next().ok_or("missing value of --bots-only-api")?;
+                    result.options.bots_only_api = match mode.as_str() {
+                        "include" => BotsOnlyApi::Include,
+                        "exclude" => BotsOnlyApi::Exclude,
+                        _ => match mode.strip_prefix("feature:") {
+                            Some(name) if !name.is_empty() => BotsOnlyApi::Feature(name.into()),
+                            _ => return Err(format!("invalid value of --bots-only-api: {mode}")),
+                        },
+                    };
+                }
+                "--blocking" => result.options.blocking_api = true,
+                "--no-docs" => result.options.docs = false,
+                "--root-path" => {
+                    let path = args.next().ok_or("missing value of --root-path")?;
+                    result.options.root_path = path;
+                }
+                "--derive" => {
+                    let traits = args.next().ok_or("missing value of --derive")?;
+                    result.options.derives = traits
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+                "--single-file" => result.single_file = true,
+                "--no-format" => result.no_format = true,
+                "--allowlist" => {
+                    let names = args.next().ok_or("missing value of --allowlist")?;
+                    result.allowlist = Some(names);
+                }
+                _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        match <[PathBuf; 2]>::try_from(paths) {
+            Ok([tl_file, out_dir]) => {
+                result.tl_file = tl_file;
+                result.out_dir = out_dir;
+                Ok(Some(result))
+            }
+            Err(_) => Err("expected a .tl file and an output directory".into()),
+        }
+    }
+}
+
+fn generate(args: &Args) -> io::Result<()> {
+    let contents = fs::read_to_string(&args.tl_file)?;
+    let classes = parse_tl_classes(contents.clone())
+        .filter_map(|c| c.map_err(|e| eprintln!("TL: parse error: {e:?}")).ok())
+        .collect::<Vec<_>>();
+    let mut definitions = parse_tl_file(contents)
+        .filter_map(|d| d.map_err(|e| eprintln!("TL: parse error: {e:?}")).ok())
+        .collect::<Vec<_>>();
+
+    if let Some(allowlist) = &args.allowlist {
+        let allowlist = allowlist
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        for name in retain_allowlisted(&mut definitions, &allowlist) {
+            eprintln!("warning: unknown definition `{name}` in the allowlist");
+        }
+    }
+
+    if args.single_file {
+        fs::create_dir_all(&args.out_dir)?;
+        let mut file = BufWriter::new(File::create(args.out_dir.join("mod.rs"))?);
+        generate_rust_code(&mut file, &definitions, &classes, &args.options)?;
+        file.flush()
+    } else {
+        generate_rust_code_split(&args.out_dir, &definitions, &classes, &args.options)
+    }
+}
+
+/// Collect the Rust files of a directory and of its subdirectories.
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            rust_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Format the generated files with rustfmt.
+fn format(out_dir: &Path) -> io::Result<bool> {
+    let mut files = Vec::new();
+    rust_files(out_dir, &mut files)?;
+    let status = Command::new("rustfmt")
+        .args(["--edition", "2024"])
+        .args(&files)
+        .status()?;
+    Ok(status.success())
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Err(e) = generate(&args) {
+        eprintln!("error: failed to generate the code: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    if !args.no_format {
+        match format(&args.out_dir) {
+            Ok(true) => {}
+            Ok(false) => eprintln!("warning: rustfmt failed, the code is not formatted"),
+            Err(e) => eprintln!("warning: failed to run rustfmt: {e}"),
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn check_default_args() {
+        let args = parse(&["api.tl", "out"]).unwrap().unwrap();
+        assert_eq!(args.tl_file, Path::new("api.tl"));
+        assert_eq!(args.out_dir, Path::new("out"));
+        assert_eq!(args.options.bots_only_api, BotsOnlyApi::Include);
+        assert!(!args.options.blocking_api);
+        assert!(args.options.docs);
+        assert_eq!(args.options.derives, ["Default"]);
+        assert!(!args.single_file);
+    }
+
+    #[test]
+    fn check_blocking() {
+        let args = parse(&["--blocking", "api.tl", "out"]).unwrap().unwrap();
+        assert!(args.options.blocking_api);
+    }
+
+    #[test]
+    fn check_derive() {
+        let args = parse(&["api.tl", "--derive", "Default, Eq,,Hash ", "out"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.options.derives, ["Default", "Eq", "Hash"]);
+
+        let args = parse(&["--derive", "", "api.tl", "out"]).unwrap().unwrap();
+        assert!(args.options.derives.is_empty());
+    }
+
+    #[test]
+    fn check_bots_only_api() {
+        for (mode, expected) in [
+            ("include", BotsOnlyApi::Include),
+            ("exclude", BotsOnlyApi::Exclude),
+            ("feature:bots", BotsOnlyApi::Feature("bots".into())),
+        ] {
+            let args = parse(&["--bots-only-api", mode, "api.tl", "out"])
+                .unwrap()
+                .unwrap();
+            assert_eq!(args.options.bots_only_api, expected);
+        }
+
+        for mode in ["feature:", "bots"] {
+            assert_eq!(
+                parse(&["--bots-only-api", mode, "api.tl", "out"]).err(),
+                Some(format!("invalid value of --bots-only-api: {mode}"))
+            );
+        }
+    }
+
+    #[test]
+    fn check_invalid_args() {
+        assert!(parse(&["--help", "--unknown"]).unwrap().is_none());
+        assert_eq!(
+            parse(&["--unknown", "api.tl", "out"]).err(),
+            Some("unknown option --unknown".into())
+        );
+        assert_eq!(
+            parse(&["api.tl", "out", "--root-path"]).err(),
+            Some("missing value of --root-path".into())
+        );
+        assert_eq!(
+            parse(&["api.tl"]).err(),
+            Some("expected a .tl file and an output directory".into())
+        );
+        assert_eq!(
+            parse(&["api.tl", "out", "extra"]).err(),
+            Some("expected a .tl file and an output directory".into())
+        );
+    }
+}
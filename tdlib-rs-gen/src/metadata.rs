@@ -8,23 +8,35 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::GenOptions;
 use crate::rustifier;
 use std::collections::{HashMap, HashSet};
-use tdlib_rs_parser::tl::{Category, Definition, Type};
+use tdlib_rs_parser::tl::{Category, Class, Definition, Type};
+
+/// The type whose variants are only required if explicitly listed.
+const UPDATE_TYPE: &str = "Update";
+/// The name of the definition of the errors returned by every function.
+const ERROR_DEFINITION: &str = "error";
 
 /// Additional metadata required by several parts of the generation.
 pub(crate) struct Metadata<'a> {
     recursing_defs: HashSet<&'a String>,
     default_impl_defs: HashSet<&'a String>,
+    float_defs: HashSet<&'a String>,
     defs_with_type: HashMap<&'a String, Vec<&'a Definition>>,
+    classes: HashMap<&'a String, &'a Class>,
+    doc_links: rustifier::DocLinks,
 }
 
 impl<'a> Metadata<'a> {
-    pub fn new(definitions: &'a [Definition]) -> Self {
+    pub fn new(definitions: &'a [Definition], classes: &'a [Class], options: &GenOptions) -> Self {
         let mut metadata = Self {
             recursing_defs: HashSet::new(),
             default_impl_defs: HashSet::new(),
+            float_defs: HashSet::new(),
             defs_with_type: HashMap::new(),
+            classes: classes.iter().map(|c| (&c.name, c)).collect(),
+            doc_links: rustifier::doc_links(definitions, options),
         };
 
         let type_definitions = definitions
@@ -57,6 +69,29 @@ impl<'a> Metadata<'a> {
             }
         });
 
+        // Propagate the floats until no more definitions contain them
+        loop {
+            let float_defs = type_definitions
+                .iter()
+                .filter(|d| !metadata.float_defs.contains(&d.name))
+                .filter(|d| {
+                    d.params.iter().any(|p| {
+                        ty_contains_float(
+                            &p.ty,
+                            &type_definition_map,
+                            &metadata.defs_with_type,
+                            &metadata.float_defs,
+                        )
+                    })
+                })
+                .map(|d| &d.name)
+                .collect::<Vec<_>>();
+            if float_defs.is_empty() {
+                break;
+            }
+            metadata.float_defs.extend(float_defs);
+        }
+
         metadata
     }
 
@@ -71,9 +106,94 @@ impl<'a> Metadata<'a> {
         self.default_impl_defs.contains(&def.name)
     }
 
+    /// Returns `true` if the `Definition` eventually contains a floating point
+    /// number, so it can't implement traits such as `Eq` or `Hash`
+    pub fn def_contains_float(&self, def: &Definition) -> bool {
+        self.float_defs.contains(&def.name)
+    }
+
+    /// Returns `true` if any of the definitions of the `Type` eventually
+    /// contains a floating point number
+    pub fn type_contains_float(&self, ty: &Type) -> bool {
+        self.defs_with_type(ty)
+            .iter()
+            .any(|d| self.def_contains_float(d))
+    }
+
     pub fn defs_with_type(&self, ty: &'a Type) -> &Vec<&Definition> {
         &self.defs_with_type[&ty.name]
     }
+
+    /// Returns the rustdoc links to the generated items
+    pub fn doc_links(&self) -> &rustifier::DocLinks {
+        &self.doc_links
+    }
+
+    /// Returns the `Class` documenting the type, if any
+    pub fn class(&self, ty: &Type) -> Option<&Class> {
+        self.classes.get(&ty.name).copied()
+    }
+
+    /// Returns the names of the definitions required by the `roots`, which
+    /// are the definitions of the types of their parameters and, for
+    /// functions, of their result and of `error`, transitively. All the
+    /// variants of a required type are included, except the ones of `Update`,
+    /// which are only included if listed in the `roots`. The names in the
+    /// `roots` that are not defined are returned separately.
+    pub fn required_defs(
+        &self,
+        definitions: &'a [Definition],
+        roots: &[&str],
+    ) -> (HashSet<&'a String>, Vec<String>) {
+        let defs_by_name = definitions
+            .iter()
+            .map(|d| (d.name.as_str(), d))
+            .collect::<HashMap<_, _>>();
+
+        let mut unknown = Vec::new();
+        let mut stack = Vec::new();
+        for root in roots {
+            match defs_by_name.get(root) {
+                Some(d) if d.category == Category::Types && d.ty.name != UPDATE_TYPE => {
+                    stack.extend(self.defs_with_type[&d.ty.name].iter().copied())
+                }
+                Some(d) => stack.push(*d),
+                None => unknown.push(root.to_string()),
+            }
+        }
+
+        let mut required = HashSet::new();
+        while let Some(def) = stack.pop() {
+            if !required.insert(&def.name) {
+                continue;
+            }
+
+            let mut types = def.params.iter().map(|p| &p.ty).collect::<Vec<_>>();
+            if def.category == Category::Functions {
+                types.push(&def.ty);
+                // The generated functions return an `Error` on failure
+                stack.extend(defs_by_name.get(ERROR_DEFINITION).copied());
+            }
+            while let Some(ty) = types.pop() {
+                if let Some(arg) = &ty.generic_arg {
+                    types.push(arg);
+                }
+                // Bare types are referenced by the name of their constructor
+                let name = match defs_by_name.get(ty.name.as_str()) {
+                    Some(d) if ty.bare => &d.ty.name,
+                    _ => &ty.name,
+                };
+                if name == UPDATE_TYPE {
+                    continue;
+                }
+                if let Some(defs) = self.defs_with_type.get(name) {
+                    stack.extend(defs.iter().copied());
+                }
+            }
+        }
+
+        (required, unknown)
+    }
 }
 
 fn def_self_references<'a>(
@@ -121,3 +241,123 @@ fn def_contains_only_bare_types<'a>(
 
     true
 }
+
+fn ty_contains_float(
+    ty: &Type,
+    definition_map: &HashMap<&String, &&Definition>,
+    defs_with_type: &HashMap<&String, Vec<&Definition>>,
+    float_defs: &HashSet<&String>,
+) -> bool {
+    if ty.name == "double" {
+        return true;
+    }
+
+    if let Some(arg) = &ty.generic_arg
+        && ty_contains_float(arg, definition_map, defs_with_type, float_defs)
+    {
+        return true;
+    }
+
+    // Bare types are referenced by the name of their constructor
+    if ty.bare {
+        definition_map
+            .get(&ty.name)
+            .is_some_and(|d| float_defs.contains(&d.name))
+    } else {
+        defs_with_type
+            .get(&ty.name)
+            .is_some_and(|defs| defs.iter().any(|d| float_defs.contains(&d.name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definitions() -> Vec<Definition> {
+        let mut definitions = [
+            "error code:int32 message:string = Error",
+            "message id:int53 content:MessageContent = Message",
+            "messageText text:string = MessageContent",
+            "messagePhoto caption:string = MessageContent",
+            "user id:int53 = User",
+            "updateNewMessage message:message = Update",
+            "updateUser user:User = Update",
+            "updates updates:vector<Update> = Updates",
+            "getMessage chat_id:int53 message_id:int53 = Message",
+            "getMessageContent message_id:int53 = MessageContent",
+            "getCurrentState = Updates",
+        ]
+        .map(|d| d.parse::<Definition>().unwrap())
+        .into_iter()
+        .collect::<Vec<_>>();
+        for d in definitions.iter_mut().filter(|d| d.name.starts_with("get")) {
+            d.category = Category::Functions;
+        }
+        definitions
+    }
+
+    fn required_defs(roots: &[&str]) -> (Vec<String>, Vec<String>) {
+        let definitions = definitions();
+        let metadata = Metadata::new(&definitions, &[], &GenOptions::default());
+        let (required, unknown) = metadata.required_defs(&definitions, roots);
+        let mut required = required.into_iter().cloned().collect::<Vec<_>>();
+        required.sort();
+        (required, unknown)
+    }
+
+    #[test]
+    fn check_required_defs_of_function() {
+        let (required, unknown) = required_defs(&["getMessage"]);
+        assert_eq!(
+            required,
+            [
+                "error",
+                "getMessage",
+                "message",
+                "messagePhoto",
+                "messageText"
+            ]
+        );
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn check_required_defs_of_function_returning_class() {
+        let (required, _) = required_defs(&["getMessageContent"]);
+        assert_eq!(
+            required,
+            ["error", "getMessageContent", "messagePhoto", "messageText"]
+        );
+    }
+
+    #[test]
+    fn check_required_defs_skip_unlisted_updates() {
+        let (required, _) = required_defs(&["getCurrentState"]);
+        assert_eq!(required, ["error", "getCurrentState", "updates"]);
+    }
+
+    #[test]
+    fn check_required_defs_of_update() {
+        // The bare `message` parameter requires the `message` constructor,
+        // and the other updates are not required
+        let (required, _) = required_defs(&["updateNewMessage"]);
+        assert_eq!(
+            required,
+            ["message", "messagePhoto", "messageText", "updateNewMessage"]
+        );
+    }
+
+    #[test]
+    fn check_required_defs_of_type() {
+        let (required, _) = required_defs(&["messageText"]);
+        assert_eq!(required, ["messagePhoto", "messageText"]);
+    }
+
+    #[test]
+    fn check_required_defs_unknown() {
+        let (required, unknown) = required_defs(&["user", "getUser"]);
+        assert_eq!(required, ["user"]);
+        assert_eq!(unknown, ["getUser"]);
+    }
+}
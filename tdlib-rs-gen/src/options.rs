@@ -0,0 +1,197 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The options of the code generation.
+
+/// The traits that can't be derived by types containing floating point
+/// numbers.
+const FLOAT_INCOMPATIBLE_DERIVES: [&str; 3] = ["Eq", "Hash", "Ord"];
+
+/// How the definitions and the parameters only used by bots are generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BotsOnlyApi {
+    /// Don't generate them.
+    Exclude,
+    /// Generate them like the other definitions and parameters.
+    Include,
+    /// Generate them behind `#[cfg(feature = "<name>")]`, with a `doc(cfg)`
+    /// badge when the documentation is built with `--cfg docsrs`.
+    Feature(String),
+}
+
+/// The options controlling the generated code.
+///
+/// # Examples
+///
+/// ```
+/// use tdlib_rs_gen::GenOptions;
+///
+/// let options = GenOptions::default()
+///     .derives(["Default", "Eq", "Hash"])
+///     .root_path("crate::tdlib");
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct GenOptions {
+    /// How the definitions and the parameters only used by bots are
+    /// generated.
+    pub bots_only_api: BotsOnlyApi,
+    /// Generate the `blocking` module, with a synchronous version of every
+    /// function.
+    pub blocking_api: bool,
+    /// Generate the documentation of the items from the TL schema.
+    pub docs: bool,
+    /// The path of the module containing the generated modules, used to
+    /// refer to their items, such as `crate` for `crate::types::Chat`.
+    /// The generated functions send their requests with the `send_request`
+    /// and `send_request_blocking` functions of this module, such as
+    /// `crate::send_request`.
+    pub root_path: String,
+    /// The traits derived by the `types` and the `enums` in addition to
+    /// `Clone`, `Debug`, `PartialEq`, `Deserialize` and `Serialize`. A trait
+    /// is only derived by the items that can derive it: `Default` is not
+    /// derived by the enums, and `Eq`, `Hash` and `Ord` are not derived by
+    /// the items containing floating point numbers.
+    pub derives: Vec<String>,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        Self {
+            bots_only_api: BotsOnlyApi::Feature("bots-only-api".into()),
+            blocking_api: false,
+            docs: true,
+            root_path: "crate".into(),
+            derives: vec!["Default".into()],
+        }
+    }
+}
+
+impl GenOptions {
+    /// Sets how the definitions and the parameters only used by bots are
+    /// generated.
+    pub fn bots_only_api(mut self, bots_only_api: BotsOnlyApi) -> Self {
+        self.bots_only_api = bots_only_api;
+        self
+    }
+
+    /// Sets whether the `blocking` module is generated.
+    pub fn blocking_api(mut self, blocking_api: bool) -> Self {
+        self.blocking_api = blocking_api;
+        self
+    }
+
+    /// Sets whether the documentation of the items is generated.
+    pub fn docs(mut self, docs: bool) -> Self {
+        self.docs = docs;
+        self
+    }
+
+    /// Sets the path of the module containing the generated modules.
+    pub fn root_path(mut self, root_path: impl Into<String>) -> Self {
+        self.root_path = root_path.into();
+        self
+    }
+
+    /// Sets the traits derived by the `types` and the `enums` in addition to
+    /// the required ones.
+    pub fn derives<I>(mut self, derives: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.derives = derives.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns the path of a generated module, such as `crate::types`.
+    pub(crate) fn module_path(&self, module: &str) -> String {
+        format!("{}::{}", self.root_path, module)
+    }
+
+    /// Returns `true` if a definition or a parameter must be generated,
+    /// given whether it's only used by bots.
+    pub(crate) fn generates(&self, bots_only: bool) -> bool {
+        !bots_only || self.bots_only_api != BotsOnlyApi::Exclude
+    }
+
+    /// Returns `true` if the documentation can link to a definition, given
+    /// whether it's only used by bots.
+    pub(crate) fn links(&self, bots_only: bool) -> bool {
+        !bots_only || self.bots_only_api == BotsOnlyApi::Include
+    }
+
+    /// Returns the attributes of an item only used by bots, one per line,
+    /// including the `doc(cfg)` badge if `badge` is set.
+    pub(crate) fn bots_only_attrs(&self, bots_only: bool, indent: &str, badge: bool) -> String {
+        match &self.bots_only_api {
+            BotsOnlyApi::Feature(name) if bots_only => {
+                let mut attrs = format!("{indent}#[cfg(feature = \"{name}\")]\n");
+                if badge {
+                    attrs.push_str(&format!(
+                        "{indent}#[cfg_attr(docsrs, doc(cfg(feature = \"{name}\")))]\n"
+                    ));
+                }
+                attrs
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Returns the extra derives of an item, given whether it can derive
+    /// `Default` and whether it contains floating point numbers.
+    pub(crate) fn item_derives(&self, can_derive_default: bool, contains_float: bool) -> Vec<&str> {
+        self.derives
+            .iter()
+            .map(String::as_str)
+            .filter(|&d| can_derive_default || d != "Default")
+            .filter(|d| !contains_float || !FLOAT_INCOMPATIBLE_DERIVES.contains(d))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_derives() {
+        let options = GenOptions::default().derives(["Default", "Eq", "PartialOrd"]);
+        assert_eq!(
+            options.item_derives(true, false),
+            ["Default", "Eq", "PartialOrd"]
+        );
+        assert_eq!(options.item_derives(false, false), ["Eq", "PartialOrd"]);
+        assert_eq!(options.item_derives(true, true), ["Default", "PartialOrd"]);
+    }
+
+    #[test]
+    fn check_bots_only_attrs() {
+        let options = GenOptions::default();
+        assert_eq!(options.bots_only_attrs(false, "    ", true), "");
+        assert_eq!(
+            options.bots_only_attrs(true, "    ", true),
+            "    #[cfg(feature = \"bots-only-api\")]\n    \
+             #[cfg_attr(docsrs, doc(cfg(feature = \"bots-only-api\")))]\n"
+        );
+        assert_eq!(
+            options.bots_only_attrs(true, "", false),
+            "#[cfg(feature = \"bots-only-api\")]\n"
+        );
+
+        let options = GenOptions::default().bots_only_api(BotsOnlyApi::Include);
+        assert!(options.generates(true));
+        assert_eq!(options.bots_only_attrs(true, "    ", true), "");
+    }
+
+    #[test]
+    fn check_module_path() {
+        let options = GenOptions::default().root_path("crate::tdlib");
+        assert_eq!(options.module_path("types"), "crate::tdlib::types");
+    }
+}
@@ -18,8 +18,17 @@
 //! * `variant_name` for use inside `enum` variants (`Foo`).
 //! * `item_path` for use as a qualified item path (`Vec::<u8>`).
 //! * `attr_name` for use as an attribute name (`foo_bar: ()`).
+//!
+//! Documentation is rustified with `description`, which turns the names of
+//! the known definitions into intra-doc links.
+
+use crate::GenOptions;
+use std::collections::HashMap;
+use tdlib_rs_parser::tl::{Category, Class, Definition, Parameter, Type};
 
-use tdlib_rs_parser::tl::{Definition, Parameter, Type};
+/// The rustdoc links to the generated items, keyed by the name of their
+/// definition (`getChatHistory`).
+pub type DocLinks = HashMap<String, String>;
 
 /// Get the rusty type name for a certain definition, excluding namespace.
 ///
@@ -71,12 +80,78 @@ fn rusty_type_name(name: &str) -> String {
     result
 }
 
+/// Get the rustdoc links to the items generated for the definitions.
+///
+/// Only names with several words (`getChatHistory`, `messageText`) are linked,
+/// since single words (`message`, `chat`) are mostly used as plain English.
+pub fn doc_links(definitions: &[Definition], options: &GenOptions) -> DocLinks {
+    definitions
+        .iter()
+        .filter(|d| {
+            d.name.starts_with(|c: char| c.is_ascii_lowercase())
+                && d.name.contains(|c: char| c.is_ascii_uppercase())
+        })
+        .filter(|d| options.links(definitions::is_for_bots_only(d)))
+        .filter_map(|d| {
+            let link = match d.category {
+                Category::Functions => {
+                    format!(
+                        "[`{0}`]({1}::{0})",
+                        definitions::function_name(d),
+                        options.module_path("functions")
+                    )
+                }
+                Category::Types if crate::ignore_type(&d.ty) => return None,
+                Category::Types if d.params.is_empty() => format!(
+                    "[`{0}::{1}`]({2}::{0}::{1})",
+                    types::type_name(&d.ty),
+                    definitions::variant_name(d),
+                    options.module_path("enums")
+                ),
+                Category::Types => {
+                    format!(
+                        "[`{0}`]({1}::{0})",
+                        definitions::type_name(d),
+                        options.module_path("types")
+                    )
+                }
+            };
+            Some((d.name.clone(), link))
+        })
+        .collect()
+}
+
+/// Replace the names of the known definitions in a string with their links,
+/// except when they are part of a URL.
+fn link_names(doc: &str, links: &DocLinks) -> String {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut result = String::with_capacity(doc.len());
+    let mut rest = doc;
+
+    while let Some(start) = rest.find(is_word_char) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+
+        let in_url = result.ends_with(['/', '.', '=', '?', '&', '#']) || after.starts_with('/');
+        match links.get(word) {
+            Some(link) if !in_url => result.push_str(link),
+            _ => result.push_str(word),
+        }
+        rest = after;
+    }
+    result.push_str(rest);
+
+    result
+}
+
 /// Get the rusty documentation from a string.
-fn rusty_doc(indent: &str, doc: &str) -> String {
+fn rusty_doc(indent: &str, doc: &str, links: &DocLinks) -> String {
     format!(
         "{}/// {}",
         indent,
-        doc.replace('\n', &format!("\n{indent}/// "))
+        link_names(doc, links).replace('\n', &format!("\n{indent}/// "))
     )
 }
 
@@ -102,10 +177,27 @@ pub mod definitions {
         result
     }
 
-    pub fn qual_name(def: &Definition) -> String {
-        let mut result = String::new();
-        result.push_str("crate::types::");
-        result.push_str(&type_name(def));
+    pub fn qual_name(def: &Definition, root: &str) -> String {
+        format!("{}::types::{}", root, type_name(def))
+    }
+
+    /// Get the name of the variant in snake case, for use in method names
+    /// (`is_foo`, `as_foo`).
+    pub fn variant_method_name(def: &Definition) -> String {
+        let name = variant_name(def);
+        let mut result = String::with_capacity(name.len() + 4);
+
+        name.chars().for_each(|c| {
+            if c.is_ascii_uppercase() {
+                if !result.is_empty() {
+                    result.push('_');
+                }
+                result.push(c.to_ascii_lowercase());
+            } else {
+                result.push(c);
+            }
+        });
+
         result
     }
 
@@ -147,8 +239,8 @@ pub mod definitions {
         .to_string()
     }
 
-    pub fn description(def: &Definition, indent: &str) -> String {
-        rusty_doc(indent, &def.description)
+    pub fn description(def: &Definition, indent: &str, links: &DocLinks) -> String {
+        rusty_doc(indent, &def.description, links)
     }
 
     pub fn is_for_bots_only(def: &Definition) -> bool {
@@ -156,6 +248,14 @@ pub mod definitions {
     }
 }
 
+pub mod classes {
+    use super::*;
+
+    pub fn description(class: &Class, indent: &str, links: &DocLinks) -> String {
+        rusty_doc(indent, &class.description, links)
+    }
+}
+
 pub mod types {
     use super::*;
 
@@ -174,23 +274,24 @@ pub mod types {
         })
     }
 
-    fn get_base_path(ty: &Type) -> String {
+    fn get_base_path(ty: &Type, root: &str) -> String {
         if let Some(name) = builtin_type(ty) {
             name.to_string()
         } else {
             let mut result = String::new();
+            result.push_str(root);
             if ty.bare {
-                result.push_str("crate::types::");
+                result.push_str("::types::");
             } else {
-                result.push_str("crate::enums::");
+                result.push_str("::enums::");
             }
             result.push_str(&type_name(ty));
             result
         }
     }
 
-    fn get_path(ty: &Type, optional_generic_arg: bool) -> String {
-        let mut result = get_base_path(ty);
+    fn get_path(ty: &Type, optional_generic_arg: bool, root: &str) -> String {
+        let mut result = get_base_path(ty, root);
 
         if let Some(generic_ty) = &ty.generic_arg {
             result.push('<');
@@ -198,7 +299,7 @@ pub mod types {
                 result.push_str("Option<");
             }
 
-            result.push_str(&qual_name(generic_ty, false));
+            result.push_str(&qual_name(generic_ty, false, root));
 
             if optional_generic_arg {
                 result.push('>');
@@ -213,23 +314,23 @@ pub mod types {
         rusty_type_name(&ty.name)
     }
 
-    pub fn qual_name(ty: &Type, optional_generic_arg: bool) -> String {
-        get_path(ty, optional_generic_arg)
+    pub fn qual_name(ty: &Type, optional_generic_arg: bool, root: &str) -> String {
+        get_path(ty, optional_generic_arg, root)
     }
 
     pub fn is_ok(ty: &Type) -> bool {
         ty.name == "Ok"
     }
 
-    pub(super) fn serde_as(ty: &Type) -> Option<String> {
+    pub(super) fn serde_as(ty: &Type, root: &str) -> Option<String> {
         if ty.name == "int64" {
             return Some("DisplayFromStr".into());
         }
 
         if let Some(generic_arg) = &ty.generic_arg
-            && let Some(serde_as) = serde_as(generic_arg)
+            && let Some(serde_as) = serde_as(generic_arg, root)
         {
-            let mut result = get_base_path(ty);
+            let mut result = get_base_path(ty, root);
 
             result.push('<');
             result.push_str(&serde_as);
@@ -245,11 +346,11 @@ pub mod types {
 pub mod parameters {
     use super::*;
 
-    pub fn qual_name(param: &Parameter) -> String {
+    pub fn qual_name(param: &Parameter, root: &str) -> String {
         // HACK: We're just matching against specific cases because there's not a
         // documented way for knowing optional generic arguments in the tl scheme
         let optional_generic_arg = param.description.contains("; messages may be null");
-        types::qual_name(&param.ty, optional_generic_arg)
+        types::qual_name(&param.ty, optional_generic_arg, root)
     }
 
     pub fn attr_name(param: &Parameter) -> String {
@@ -279,12 +380,12 @@ pub mod parameters {
         param.description.contains("; for bots only")
     }
 
-    pub fn description(param: &Parameter, indent: &str) -> String {
-        rusty_doc(indent, &param.description)
+    pub fn description(param: &Parameter, indent: &str, links: &DocLinks) -> String {
+        rusty_doc(indent, &param.description, links)
     }
 
-    pub fn serde_as(param: &Parameter) -> Option<String> {
-        types::serde_as(&param.ty)
+    pub fn serde_as(param: &Parameter, root: &str) -> Option<String> {
+        types::serde_as(&param.ty, root)
     }
 }
 
@@ -311,7 +412,7 @@ mod tests {
     #[test]
     fn check_def_qual_name() {
         let def = "userEmpty = User".parse().unwrap();
-        let name = definitions::qual_name(&def);
+        let name = definitions::qual_name(&def, "crate");
         assert_eq!(name, "crate::types::UserEmpty");
     }
 
@@ -336,6 +437,49 @@ mod tests {
         assert_eq!(name, "PeerSelf");
     }
 
+    #[test]
+    fn check_def_variant_method_name() {
+        let def = "inputMessageText = InputMessageContent".parse().unwrap();
+        let name = definitions::variant_method_name(&def);
+        assert_eq!(name, "input_message_text");
+    }
+
+    #[test]
+    fn check_def_self_variant_method_name() {
+        let def = "inputPeerSelf = InputPeer".parse().unwrap();
+        let name = definitions::variant_method_name(&def);
+        assert_eq!(name, "peer_self");
+    }
+
+    // Documentation methods
+
+    #[test]
+    fn check_doc_links() {
+        let mut definitions = [
+            "getChatHistory chat_id:int53 = Messages",
+            "messageText text:string = MessageContent",
+            "chatListMain = ChatList",
+            "message id:int53 = Message",
+        ]
+        .map(|d| d.parse::<Definition>().unwrap());
+        definitions[0].category = Category::Functions;
+
+        let links = doc_links(&definitions, &GenOptions::default());
+        assert_eq!(links.len(), 3);
+        assert_eq!(
+            link_names("Use getChatHistory to get a message.", &links),
+            "Use [`get_chat_history`](crate::functions::get_chat_history) to get a message."
+        );
+        assert_eq!(
+            link_names("See messageText and chatListMain", &links),
+            "See [`MessageText`](crate::types::MessageText) and [`ChatList::Main`](crate::enums::ChatList::Main)"
+        );
+        assert_eq!(
+            link_names("https://t.me/getChatHistory", &links),
+            "https://t.me/getChatHistory"
+        );
+    }
+
     // Type methods
 
     #[test]
@@ -348,56 +492,56 @@ mod tests {
     #[test]
     fn check_type_qual_name() {
         let ty = "InputPeer".parse().unwrap();
-        let name = types::qual_name(&ty, false);
+        let name = types::qual_name(&ty, false, "crate");
         assert_eq!(name, "crate::enums::InputPeer");
     }
 
     #[test]
     fn check_type_qual_bare_name() {
         let ty = "ipPort".parse().unwrap();
-        let name = types::qual_name(&ty, false);
+        let name = types::qual_name(&ty, false, "crate");
         assert_eq!(name, "crate::types::IpPort");
     }
 
     #[test]
     fn check_type_bytes_qual_name() {
         let ty = "bytes".parse().unwrap();
-        let name = types::qual_name(&ty, false);
+        let name = types::qual_name(&ty, false, "crate");
         assert_eq!(name, "String");
     }
 
     #[test]
     fn check_type_large_int_qual_name() {
         let ty = "int256".parse().unwrap();
-        let name = types::qual_name(&ty, false);
+        let name = types::qual_name(&ty, false, "crate");
         assert_eq!(name, "crate::types::Int256");
     }
 
     #[test]
     fn check_type_raw_vec_qual_name() {
         let ty = "vector<long>".parse().unwrap();
-        let name = types::qual_name(&ty, false);
+        let name = types::qual_name(&ty, false, "crate");
         assert_eq!(name, "Vec<crate::types::Long>");
     }
 
     #[test]
     fn check_type_opt_raw_vec_qual_name() {
         let ty = "vector<long>".parse().unwrap();
-        let name = types::qual_name(&ty, true);
+        let name = types::qual_name(&ty, true, "crate");
         assert_eq!(name, "Vec<Option<crate::types::Long>>");
     }
 
     #[test]
     fn check_type_vec_qual_name() {
         let ty = "Vector<Bool>".parse().unwrap();
-        let name = types::qual_name(&ty, false);
+        let name = types::qual_name(&ty, false, "crate");
         assert_eq!(name, "crate::enums::Vector<bool>");
     }
 
     #[test]
     fn check_type_opt_vec_qual_name() {
         let ty = "Vector<Bool>".parse().unwrap();
-        let name = types::qual_name(&ty, true);
+        let name = types::qual_name(&ty, true, "crate");
         assert_eq!(name, "crate::enums::Vector<Option<bool>>");
     }
 
@@ -406,7 +550,7 @@ mod tests {
     #[test]
     fn check_param_qual_name() {
         let param = "pts:int".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, "crate");
         assert_eq!(name, "crate::types::Int");
     }
 
@@ -10,10 +10,13 @@
 
 //! Code to generate Rust's `struct`'s from TL definitions.
 
+use crate::GenOptions;
+use crate::chunks::Chunks;
 use crate::ignore_type;
 use crate::metadata::Metadata;
 use crate::rustifier;
 use std::io::{self, Write};
+use std::path::Path;
 use tdlib_rs_parser::tl::{Category, Definition};
 
 /// Defines the `struct` corresponding to the definition:
@@ -27,26 +30,38 @@ fn write_struct<W: Write>(
     file: &mut W,
     def: &Definition,
     metadata: &Metadata,
-    gen_bots_only_api: bool,
+    options: &GenOptions,
 ) -> io::Result<()> {
-    if rustifier::definitions::is_for_bots_only(def) && !gen_bots_only_api {
+    let bots_only = rustifier::definitions::is_for_bots_only(def);
+    if !options.generates(bots_only) {
         return Ok(());
     }
 
-    writeln!(file, "{}", rustifier::definitions::description(def, "    "))?;
+    if options.docs {
+        writeln!(
+            file,
+            "{}",
+            rustifier::definitions::description(def, "    ", metadata.doc_links())
+        )?;
+    }
+
+    write!(file, "{}", options.bots_only_attrs(bots_only, "    ", true))?;
 
     let serde_as = def
         .params
         .iter()
-        .any(|p| rustifier::parameters::serde_as(p).is_some());
+        .any(|p| rustifier::parameters::serde_as(p, &options.root_path).is_some());
 
     if serde_as {
         writeln!(file, "    #[serde_as]",)?;
     }
 
     write!(file, "    #[derive(Clone, Debug, ",)?;
-    if metadata.can_def_implement_default(def) {
-        write!(file, "Default, ",)?;
+    for derive in options.item_derives(
+        metadata.can_def_implement_default(def),
+        metadata.def_contains_float(def),
+    ) {
+        write!(file, "{derive}, ")?;
     }
     writeln!(file, "PartialEq, Deserialize, Serialize)]",)?;
 
@@ -57,17 +72,25 @@ fn write_struct<W: Write>(
     )?;
 
     for param in def.params.iter() {
-        if rustifier::parameters::is_for_bots_only(param) && !gen_bots_only_api {
+        let bots_only = rustifier::parameters::is_for_bots_only(param);
+        if !options.generates(bots_only) {
             continue;
         }
 
-        writeln!(
+        if options.docs {
+            writeln!(
+                file,
+                "{}",
+                rustifier::parameters::description(param, "        ", metadata.doc_links())
+            )?;
+        }
+
+        write!(
             file,
             "{}",
-            rustifier::parameters::description(param, "        ")
+            options.bots_only_attrs(bots_only, "        ", true)
         )?;
-
-        if let Some(serde_as) = rustifier::parameters::serde_as(param) {
+        if let Some(serde_as) = rustifier::parameters::serde_as(param, &options.root_path) {
             writeln!(file, "        #[serde_as(as = \"{serde_as}\")]")?;
         }
         write!(
@@ -80,7 +103,11 @@ fn write_struct<W: Write>(
         if is_optional {
             write!(file, "Option<")?;
         }
-        write!(file, "{}", rustifier::parameters::qual_name(param))?;
+        write!(
+            file,
+            "{}",
+            rustifier::parameters::qual_name(param, &options.root_path)
+        )?;
         if is_optional {
             write!(file, ">")?;
         }
@@ -97,18 +124,19 @@ fn write_definition<W: Write>(
     file: &mut W,
     def: &Definition,
     metadata: &Metadata,
-    gen_bots_only_api: bool,
+    options: &GenOptions,
 ) -> io::Result<()> {
-    write_struct(file, def, metadata, gen_bots_only_api)?;
+    write_struct(file, def, metadata, options)?;
     Ok(())
 }
 
 /// Write the entire module dedicated to types.
 pub(crate) fn write_types_mod<W: Write>(
-    mut file: &mut W,
+    file: &mut W,
     definitions: &[Definition],
     metadata: &Metadata,
-    gen_bots_only_api: bool,
+    options: &GenOptions,
+    out_dir: Option<&Path>,
 ) -> io::Result<()> {
     // Begin outermost mod
     writeln!(file, "#[allow(clippy::all)]")?;
@@ -120,9 +148,12 @@ pub(crate) fn write_types_mod<W: Write>(
         .iter()
         .filter(|d| d.category == Category::Types && !ignore_type(&d.ty) && !d.params.is_empty());
 
+    let mut chunks = Chunks::new("types", out_dir);
     for definition in types {
-        write_definition(&mut file, definition, metadata, gen_bots_only_api)?;
+        let chunk = chunks.chunk(&rustifier::definitions::type_name(definition));
+        write_definition(chunk, definition, metadata, options)?;
     }
+    chunks.write(file)?;
 
     // End outermost mod
     writeln!(file, "}}")
@@ -16,15 +16,20 @@
 //! yield [`Definition`]s containing all the information you would possibly
 //! need to later use somewhere else (for example, to generate code).
 //!
+//! The documentation of the abstract types shared by several definitions can
+//! be parsed with [`parse_tl_classes`], which yields [`Class`]es.
+//!
 //! [Type Language]: https://core.telegram.org/mtproto/TL
 //! [`parse_tl_file`]: fn.parse_tl_file.html
 //! [`Definition`]: tl/struct.Definition.html
+//! [`parse_tl_classes`]: fn.parse_tl_classes.html
+//! [`Class`]: tl/struct.Class.html
 pub mod errors;
 pub mod tl;
 mod tl_iterator;
 
 use errors::ParseError;
-use tl::Definition;
+use tl::{CLASS_PREFIX, Class, Definition};
 use tl_iterator::TlIterator;
 
 /// Parses a file full of [Type Language] definitions.
@@ -53,3 +58,38 @@ use tl_iterator::TlIterator;
 pub fn parse_tl_file(contents: String) -> impl Iterator<Item = Result<Definition, ParseError>> {
     TlIterator::new(contents)
 }
+
+/// Parses the `//@class` comments of a file full of [Type Language]
+/// definitions, which document the types shared by several definitions.
+///
+/// # Examples
+///
+/// ```
+/// use tdlib_rs_parser::parse_tl_classes;
+///
+/// let contents = "
+/// //@class ChatList @description Describes a list of chats
+///
+/// //@description A main list of chats
+/// chatListMain = ChatList;
+/// ";
+///
+/// let classes = parse_tl_classes(contents.into()).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(classes[0].name, "ChatList");
+/// assert_eq!(classes[0].description, "Describes a list of chats");
+/// ```
+///
+/// [Type Language]: https://core.telegram.org/mtproto/TL
+pub fn parse_tl_classes(contents: String) -> impl Iterator<Item = Result<Class, ParseError>> {
+    let mut classes = Vec::new();
+    let mut lines = contents.lines().map(str::trim).peekable();
+    while let Some(line) = lines.find(|line| line.starts_with(CLASS_PREFIX)) {
+        let mut class = line.to_owned();
+        while let Some(line) = lines.next_if(|line| line.starts_with("//-")) {
+            class.push('\n');
+            class.push_str(line);
+        }
+        classes.push(class);
+    }
+    classes.into_iter().map(|class| class.parse())
+}
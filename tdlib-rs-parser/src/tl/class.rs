@@ -0,0 +1,113 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::str::FromStr;
+
+use crate::errors::ParseError;
+
+/// The prefix of the comments documenting a class.
+pub(crate) const CLASS_PREFIX: &str = "//@class";
+
+/// A class, the abstract type shared by several [Type Language] definitions,
+/// as documented by a `//@class Name @description ...` comment.
+///
+/// [Type Language]: https://core.telegram.org/mtproto/TL
+#[derive(Debug, PartialEq)]
+pub struct Class {
+    /// The name of this class, which is the name of the type of its
+    /// definitions.
+    pub name: String,
+
+    /// The description of this class.
+    pub description: String,
+}
+
+impl FromStr for Class {
+    type Err = ParseError;
+
+    /// Parses a class comment, possibly continued on the following lines
+    /// starting with `//-`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tdlib_rs_parser::tl::Class;
+    ///
+    /// let class = "//@class ChatList @description Describes a list of chats"
+    ///     .parse::<Class>()
+    ///     .unwrap();
+    /// assert_eq!(class.name, "ChatList");
+    /// ```
+    fn from_str(class: &str) -> Result<Self, Self::Err> {
+        let class = class.trim();
+        if class.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let class = class
+            .strip_prefix(CLASS_PREFIX)
+            .ok_or(ParseError::MissingName)?
+            .replace("//-", "");
+        let (name, rest) = class
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .unwrap_or((class.trim(), ""));
+        if name.is_empty() {
+            return Err(ParseError::MissingName);
+        }
+
+        let description = rest
+            .trim()
+            .strip_prefix("@description")
+            .unwrap_or_default()
+            .trim();
+
+        Ok(Class {
+            name: name.into(),
+            description: description.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_empty_class() {
+        assert_eq!(Class::from_str(""), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn parse_no_name() {
+        assert_eq!(Class::from_str("//@class"), Err(ParseError::MissingName));
+        assert_eq!(Class::from_str("foo = Bar"), Err(ParseError::MissingName));
+    }
+
+    #[test]
+    fn parse_no_description() {
+        assert_eq!(
+            Class::from_str("//@class Foo"),
+            Ok(Class {
+                name: "Foo".into(),
+                description: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_multiline_class() {
+        let class = "//@class Foo @description First line.\n//-Second line";
+        assert_eq!(
+            Class::from_str(class),
+            Ok(Class {
+                name: "Foo".into(),
+                description: "First line.\nSecond line".into(),
+            })
+        );
+    }
+}
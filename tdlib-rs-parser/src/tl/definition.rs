@@ -66,9 +66,11 @@ impl FromStr for Definition {
             return Err(ParseError::Empty);
         }
 
-        let (definition, mut docs) = {
+        let (definition, mut docs, class_description) = {
             let mut docs = HashMap::new();
             let mut comments_end = 0;
+            let mut in_class = false;
+            let mut class_description = None;
 
             if let Some(start) = definition.rfind("//")
                 && let Some(end) = definition[start..].find('\n')
@@ -87,16 +89,28 @@ impl FromStr for Definition {
 
                 let comment = definition[start + 1..end].replace("//-", "");
                 let comment = comment.replace("//", "").trim().to_owned();
+                offset = end;
+
+                // The description of a class belongs to the class, and is only
+                // kept by the definition following it if it has none
+                if comment.starts_with("class ") {
+                    in_class = true;
+                    continue;
+                }
+                if in_class && let Some(description) = comment.strip_prefix("description") {
+                    in_class = false;
+                    class_description = Some(description.trim().to_owned());
+                    continue;
+                }
+
                 if let Some((name, content)) = comment.split_once(' ') {
                     docs.insert(name.into(), content.into());
                 } else {
                     docs.insert(comment, String::new());
                 }
-
-                offset = end;
             }
 
-            (&definition[comments_end..], docs)
+            (&definition[comments_end..], docs, class_description)
         };
 
         // Parse `(left = ty)`
@@ -125,7 +139,10 @@ impl FromStr for Definition {
         }
 
         // Parse `description`
-        let description = docs.remove("description").unwrap_or_default();
+        let description = docs
+            .remove("description")
+            .or(class_description)
+            .unwrap_or_default();
 
         // Parse `middle`
         let params = middle
@@ -298,6 +315,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_after_class() {
+        let def = "
+            //@class Type @description This is a class description
+
+            name = Type";
+        assert_eq!(
+            Definition::from_str(def).unwrap().description,
+            "This is a class description"
+        );
+
+        let def = "
+            //@class Type @description This is a class description
+
+            //@description This is a test description
+            name = Type";
+        assert_eq!(
+            Definition::from_str(def).unwrap().description,
+            "This is a test description"
+        );
+    }
+
     #[test]
     fn test_to_string() {
         let def = "name pname:Vector<X> = Type";
@@ -13,11 +13,14 @@
 //!
 //! [Type Language]: https://core.telegram.org/mtproto/TL
 mod category;
+mod class;
 mod definition;
 mod parameter;
 mod ty;
 
 pub use category::Category;
+pub(crate) use class::CLASS_PREFIX;
+pub use class::Class;
 pub use definition::Definition;
 pub use parameter::Parameter;
 pub use ty::Type;
@@ -9,25 +9,217 @@
 // except according to those terms.
 use std::env;
 use std::fs::File;
-use std::io::{BufWriter, Read, Write};
+use std::io::{Read, Write};
 use std::path::Path;
-use tdlib_rs_gen::generate_rust_code;
-use tdlib_rs_parser::parse_tl_file;
-use tdlib_rs_parser::tl::Definition;
+use tdlib_rs_gen::{GenOptions, generate_rust_code_split, retain_allowlisted};
+use tdlib_rs_parser::tl::{Category, Class, Definition};
+use tdlib_rs_parser::{parse_tl_classes, parse_tl_file};
 
 #[allow(dead_code)]
 #[cfg(not(any(feature = "docs", feature = "pkg-config")))]
 /// The version of the TDLib library.
 const TDLIB_VERSION: &str = "1.8.61";
 
-/// Load the type language definitions from a certain file.
+/// The definitions used by the crate whatever the enabled features: the
+/// errors of the functions, and an update so that `Update` is never empty.
+const CORE_DEFINITIONS: &[&str] = &["error", "updateAuthorizationState"];
+
+/// The definitions used by the `state` module.
+const STATE_DEFINITIONS: &[&str] = &[
+    "chatListMain",
+    "getOption",
+    "loadChats",
+    "setOption",
+    "updateBasicGroup",
+    "updateChatAccentColors",
+    "updateChatActionBar",
+    "updateChatAddedToList",
+    "updateChatAvailableReactions",
+    "updateChatBackground",
+    "updateChatBlockList",
+    "updateChatBusinessBotManageBar",
+    "updateChatDefaultDisableNotification",
+    "updateChatDraftMessage",
+    "updateChatEmojiStatus",
+    "updateChatHasProtectedContent",
+    "updateChatHasScheduledMessages",
+    "updateChatIsMarkedAsUnread",
+    "updateChatIsTranslatable",
+    "updateChatLastMessage",
+    "updateChatMessageAutoDeleteTime",
+    "updateChatMessageSender",
+    "updateChatNotificationSettings",
+    "updateChatPendingJoinRequests",
+    "updateChatPermissions",
+    "updateChatPhoto",
+    "updateChatPosition",
+    "updateChatReadInbox",
+    "updateChatReadOutbox",
+    "updateChatRemovedFromList",
+    "updateChatReplyMarkup",
+    "updateChatTheme",
+    "updateChatTitle",
+    "updateChatUnreadMentionCount",
+    "updateChatUnreadReactionCount",
+    "updateChatVideoChat",
+    "updateChatViewAsTopics",
+    "updateMessageMentionRead",
+    "updateMessageUnreadReactions",
+    "updateNewChat",
+    "updateOption",
+    "updateSupergroup",
+    "updateUser",
+    "updateUserStatus",
+];
+
+/// The definitions used by the `files` module.
+const FILES_DEFINITIONS: &[&str] = &["downloadFile", "preliminaryUploadFile", "updateFile"];
+
+/// The definitions used by the `dispatcher` module.
+const DISPATCHER_DEFINITIONS: &[&str] = &[
+    "deleteMessages",
+    "messageText",
+    "updateAuthorizationState",
+    "updateBasicGroup",
+    "updateChatAction",
+    "updateChatAddedToList",
+    "updateChatDraftMessage",
+    "updateChatLastMessage",
+    "updateChatMember",
+    "updateChatNotificationSettings",
+    "updateChatPermissions",
+    "updateChatPhoto",
+    "updateChatPosition",
+    "updateChatReadInbox",
+    "updateChatReadOutbox",
+    "updateChatRemovedFromList",
+    "updateChatTitle",
+    "updateConnectionState",
+    "updateDeleteMessages",
+    "updateFile",
+    "updateMessageContent",
+    "updateMessageContentOpened",
+    "updateMessageEdited",
+    "updateMessageInteractionInfo",
+    "updateMessageIsPinned",
+    "updateMessageMentionRead",
+    "updateMessageSendAcknowledged",
+    "updateMessageSendFailed",
+    "updateMessageSendSucceeded",
+    "updateMessageUnreadReactions",
+    "updateNewCallbackQuery",
+    "updateNewChat",
+    "updateNewChatJoinRequest",
+    "updateNewInlineQuery",
+    "updateNewMessage",
+    "updateOption",
+    "updateSupergroup",
+    "updateUser",
+    "updateUserStatus",
+];
+
+/// The definitions used by the `commands` module.
+const COMMANDS_DEFINITIONS: &[&str] = &[
+    "botCommand",
+    "botCommandScopeAllPrivateChats",
+    "getMe",
+    "setCommands",
+    "updateNewMessage",
+];
+
+/// The definitions used by the `keyboard` module.
+const KEYBOARD_DEFINITIONS: &[&str] = &[
+    "answerCallbackQuery",
+    "inlineKeyboardButton",
+    "replyMarkupInlineKeyboard",
+    "updateNewCallbackQuery",
+];
+
+/// The definitions used by the `formatting` module.
+const FORMATTING_DEFINITIONS: &[&str] = &["formattedText", "textEntity"];
+
+/// The definitions used by the `pagination` module.
+const PAGINATION_DEFINITIONS: &[&str] = &[
+    "getChatHistory",
+    "getChatJoinRequests",
+    "getSupergroupMembers",
+    "searchChatMessages",
+    "searchMessages",
+    "supergroupMembersFilterRecent",
+];
+
+/// The definitions used by the `conversation` module.
+const CONVERSATION_DEFINITIONS: &[&str] = &[
+    "formattedText",
+    "inputMessageText",
+    "sendMessage",
+    "updateNewMessage",
+];
+
+/// The definitions used by the `send` module.
+const SEND_DEFINITIONS: &[&str] = &[
+    "sendMessage",
+    "updateAuthorizationState",
+    "updateMessageSendFailed",
+    "updateMessageSendSucceeded",
+];
+
+/// The definitions used by the `connection` module.
+const CONNECTION_DEFINITIONS: &[&str] = &["updateAuthorizationState", "updateConnectionState"];
+
+/// The definitions always generated even if they are not listed in
+/// `TDLIB_RS_ALLOWLIST`, with whether the module using them is enabled.
+/// A module must list here the functions, updates and types it refers to.
+const REQUIRED_DEFINITIONS: &[(bool, &[&str])] = &[
+    (true, CORE_DEFINITIONS),
+    (cfg!(feature = "state"), STATE_DEFINITIONS),
+    (cfg!(feature = "files"), FILES_DEFINITIONS),
+    (cfg!(feature = "dispatcher"), DISPATCHER_DEFINITIONS),
+    (
+        cfg!(all(feature = "bots-only-api", feature = "dispatcher")),
+        COMMANDS_DEFINITIONS,
+    ),
+    (cfg!(feature = "keyboard"), KEYBOARD_DEFINITIONS),
+    (cfg!(feature = "formatting"), FORMATTING_DEFINITIONS),
+    (cfg!(feature = "pagination"), PAGINATION_DEFINITIONS),
+    (cfg!(feature = "conversation"), CONVERSATION_DEFINITIONS),
+    (cfg!(feature = "send"), SEND_DEFINITIONS),
+    (cfg!(feature = "connection"), CONNECTION_DEFINITIONS),
+];
+
+/// Writes the names of the generated updates, sorted, to `allowlist.rs`, so
+/// that `receive` can skip the updates not listed in `TDLIB_RS_ALLOWLIST`.
+fn write_allowlisted_updates(out_dir: &Path, definitions: &[Definition]) -> std::io::Result<()> {
+    let mut updates = definitions
+        .iter()
+        .filter(|d| d.category == Category::Types && d.ty.name == "Update")
+        .map(|d| format!("    \"{}\",\n", d.name))
+        .collect::<Vec<_>>();
+    updates.sort();
+    let mut file = File::create(out_dir.join("allowlist.rs"))?;
+    writeln!(file, "/// The names of the generated updates, sorted.")?;
+    writeln!(file, "pub(crate) const UPDATES: &[&str] = &[")?;
+    file.write_all(updates.concat().as_bytes())?;
+    writeln!(file, "];")
+}
+
+/// Load the type language definitions and classes from a certain file.
 /// Parse errors will be printed to `stderr`, and only the
 /// valid results will be returned.
-fn load_tl(file: &str) -> std::io::Result<Vec<Definition>> {
+fn load_tl(file: &str) -> std::io::Result<(Vec<Definition>, Vec<Class>)> {
     let mut file = File::open(file)?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
-    Ok(parse_tl_file(contents)
+    let classes = parse_tl_classes(contents.clone())
+        .filter_map(|c| match c {
+            Ok(c) => Some(c),
+            Err(e) => {
+                eprintln!("TL: parse error: {e:?}");
+                None
+            }
+        })
+        .collect();
+    let definitions = parse_tl_file(contents)
         .filter_map(|d| match d {
             Ok(d) => Some(d),
             Err(e) => {
@@ -35,7 +227,8 @@ fn load_tl(file: &str) -> std::io::Result<Vec<Definition>> {
                 None
             }
         })
-        .collect())
+        .collect();
+    Ok((definitions, classes))
 }
 
 #[cfg(feature = "local-tdlib")]
@@ -256,13 +449,38 @@ fn main() -> std::io::Result<()> {
 
     let out_dir = env::var("OUT_DIR").unwrap();
 
-    let definitions = load_tl("tl/api.tl")?;
-
-    let mut file = BufWriter::new(File::create(Path::new(&out_dir).join("generated.rs"))?);
+    let (mut definitions, classes) = load_tl("tl/api.tl")?;
 
-    generate_rust_code(&mut file, &definitions, cfg!(feature = "bots-only-api"))?;
+    // It can be set to a list of the names of the functions, updates and
+    // types to generate, separated by commas or whitespaces:
+    // - export TDLIB_RS_ALLOWLIST="getChat,sendMessage,updateNewMessage"
+    // The definitions used by the enabled modules of the crate, listed in
+    // `REQUIRED_DEFINITIONS`, are always generated.
+    println!("cargo:rerun-if-env-changed=TDLIB_RS_ALLOWLIST");
+    println!("cargo:rustc-check-cfg=cfg(tdlib_rs_allowlist)");
+    if let Ok(allowlist) = env::var("TDLIB_RS_ALLOWLIST") {
+        println!("cargo:rustc-cfg=tdlib_rs_allowlist");
+        let required = REQUIRED_DEFINITIONS
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .flat_map(|(_, names)| names.iter().copied());
+        let allowlist = allowlist
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+            .chain(required)
+            .collect::<Vec<_>>();
+        for name in retain_allowlisted(&mut definitions, &allowlist) {
+            println!("cargo:warning=TDLIB_RS_ALLOWLIST: unknown definition `{name}`");
+        }
+        write_allowlisted_updates(Path::new(&out_dir), &definitions)?;
+    }
 
-    file.flush()?;
+    generate_rust_code_split(
+        &Path::new(&out_dir).join("generated"),
+        &definitions,
+        &classes,
+        &GenOptions::default().blocking_api(cfg!(feature = "blocking")),
+    )?;
 
     Ok(())
 }
@@ -0,0 +1,79 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The blocking module contains a synchronous version of every function of
+//! the [`functions`](crate::functions) module, for applications that don't
+//! want to depend on an async executor.
+//!
+//! Responses are delivered by [`receive`](crate::receive), so a thread must
+//! keep calling it while the blocking functions are waiting. The
+//! [`spawn_receiver`] function starts such a thread:
+//!
+//! ```no_run
+//! use tdlib_rs::{blocking, enums::User};
+//!
+//! let client_id = tdlib_rs::create_client();
+//! let receiver = blocking::spawn_receiver(|update, _client_id| {
+//!     println!("{update:?}");
+//! });
+//!
+//! blocking::set_log_verbosity_level(2, client_id).unwrap();
+//! // ...
+//! let User::User(me) = blocking::get_me(client_id).unwrap();
+//! println!("Hi, I'm {}", me.first_name);
+//!
+//! receiver.stop();
+//! ```
+//!
+//! Note that the blocking functions must never be called from the handler
+//! given to [`spawn_receiver`], since the response would never be received.
+use crate::enums::Update;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+pub use crate::generated::blocking::*;
+
+/// A background thread that receives updates and responses from TdLib.
+/// It's created by [`spawn_receiver`].
+pub struct Receiver {
+    run_flag: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl Receiver {
+    /// Stop receiving and wait for the thread to end its execution.
+    pub fn stop(self) {
+        self.run_flag.store(false, Ordering::Release);
+        if self.handle.join().is_err() {
+            log::warn!("The receiver thread panicked");
+        }
+    }
+}
+
+/// Spawn a thread that keeps calling [`receive`](crate::receive) until
+/// [`Receiver::stop`] is called. Responses are dispatched to the waiting
+/// functions, and every update is passed to `handler` together with the
+/// associated `client_id`.
+pub fn spawn_receiver<F>(mut handler: F) -> Receiver
+where
+    F: FnMut(Update, i32) + Send + 'static,
+{
+    let run_flag = Arc::new(AtomicBool::new(true));
+    let run_flag_clone = run_flag.clone();
+
+    let handle = thread::spawn(move || {
+        while run_flag_clone.load(Ordering::Acquire) {
+            if let Some((update, client_id)) = crate::receive() {
+                handler(update, client_id);
+            }
+        }
+    });
+
+    Receiver { run_flag, handle }
+}
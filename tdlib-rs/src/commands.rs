@@ -0,0 +1,317 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The commands module contains the handlers of bot commands, such as
+//! `/start@my_bot arg`, received in text messages. The handlers are
+//! registered in a [`Dispatcher`], and [`Commands`] keeps their descriptions
+//! to send them to Telegram.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # async fn run(client_id: i32) {
+//! use tdlib_rs::commands::Commands;
+//! use tdlib_rs::dispatcher::{Context, Dispatcher};
+//! use tdlib_rs::enums::BotCommandScope;
+//! use tdlib_rs::types::Message;
+//!
+//! let mut dispatcher = Dispatcher::new(());
+//! let mut commands = Commands::new();
+//! commands.load_bot_username(client_id).await.unwrap();
+//! commands
+//!     .command(
+//!         &mut dispatcher,
+//!         "add",
+//!         "Add two numbers",
+//!         |ctx: Context<()>, message: Message, (a, b): (i32, i32)| async move {
+//!             println!("{} + {} = {}", a, b, a + b);
+//!         },
+//!     )
+//!     .describe(Some(BotCommandScope::AllPrivateChats), "it", "Somma due numeri");
+//! commands.sync_commands(client_id).await.unwrap();
+//!
+//! dispatcher.listen(client_id).await;
+//! # }
+//! ```
+use crate::dispatcher::{Context, Dispatcher, Flow, IntoFlow};
+use crate::enums::{self, BotCommandScope, MessageContent, Update};
+use crate::functions;
+use crate::types::{BotCommand, Error, Message, UpdateNewMessage};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// A bot command parsed from a text, such as `/start@my_bot arg`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Command<'a> {
+    /// The name of the command, without the leading `/`.
+    pub name: &'a str,
+    /// The username of the bot the command is addressed to, if specified.
+    pub bot_username: Option<&'a str>,
+    /// The text following the command, with no leading whitespace.
+    pub args: &'a str,
+}
+
+/// Parse the bot command at the beginning of the text of a message, if any.
+fn message_command(message: &Message) -> Option<Command<'_>> {
+    match &message.content {
+        MessageContent::MessageText(content) => parse(&content.text.text),
+        _ => None,
+    }
+}
+
+/// Parse a bot command from the beginning of a text.
+pub fn parse(text: &str) -> Option<Command<'_>> {
+    let text = text.strip_prefix('/')?;
+    let (command, args) = match text.find(char::is_whitespace) {
+        Some(end) => (&text[..end], text[end..].trim_start()),
+        None => (text, ""),
+    };
+    let (name, bot_username) = match command.split_once('@') {
+        Some((name, bot_username)) => (name, Some(bot_username)),
+        None => (command, None),
+    };
+    if name.is_empty() || bot_username == Some("") {
+        return None;
+    }
+    Some(Command {
+        name,
+        bot_username,
+        args,
+    })
+}
+
+/// The arguments of a command, parsed from the text following it.
+pub trait FromArgs: Sized {
+    /// Parse the arguments, returning `None` if they are invalid.
+    fn from_args(args: &str) -> Option<Self>;
+}
+
+impl FromArgs for () {
+    fn from_args(args: &str) -> Option<Self> {
+        args.is_empty().then_some(())
+    }
+}
+
+impl FromArgs for String {
+    fn from_args(args: &str) -> Option<Self> {
+        Some(args.to_string())
+    }
+}
+
+impl FromArgs for Vec<String> {
+    fn from_args(args: &str) -> Option<Self> {
+        Some(args.split_whitespace().map(String::from).collect())
+    }
+}
+
+macro_rules! from_args_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: FromStr),+> FromArgs for ($($ty,)+) {
+            fn from_args(args: &str) -> Option<Self> {
+                let mut args = args.split_whitespace();
+                let result = ($(args.next()?.parse::<$ty>().ok()?,)+);
+                args.next().is_none().then_some(result)
+            }
+        }
+    };
+}
+
+from_args_tuple!(A);
+from_args_tuple!(A, B);
+from_args_tuple!(A, B, C);
+from_args_tuple!(A, B, C, D);
+
+/// A registered command.
+pub struct CommandEntry {
+    name: String,
+    descriptions: Vec<(Option<BotCommandScope>, String, String)>,
+}
+
+impl CommandEntry {
+    /// Add a description of the command for the given scope and language,
+    /// used by [`Commands::sync_commands`]. An empty language code means all
+    /// the languages without a dedicated description.
+    pub fn describe(
+        &mut self,
+        scope: Option<BotCommandScope>,
+        language_code: &str,
+        description: &str,
+    ) -> &mut Self {
+        self.descriptions
+            .push((scope, language_code.into(), description.into()));
+        self
+    }
+}
+
+/// The bot commands registered in a [`Dispatcher`], with their descriptions.
+#[derive(Default)]
+pub struct Commands {
+    bot_username: Arc<RwLock<Option<String>>>,
+    commands: Vec<CommandEntry>,
+}
+
+impl Commands {
+    /// Create an empty list of commands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the username of the bot. Commands addressed to other bots, such as
+    /// `/start@other_bot`, are then ignored by the registered handlers.
+    pub fn set_bot_username(&mut self, bot_username: &str) {
+        *self.bot_username.write().unwrap() = Some(bot_username.into());
+    }
+
+    /// Set the username of the bot, as returned by `getMe`.
+    pub async fn load_bot_username(&mut self, client_id: i32) -> Result<(), Error> {
+        let enums::User::User(me) = functions::get_me(client_id).await?;
+        if let Some(usernames) = me.usernames {
+            self.set_bot_username(&usernames.editable_username);
+        }
+        Ok(())
+    }
+
+    /// Register in `dispatcher` a handler for a command, with a description
+    /// used for the default scope and language. The handler is called with
+    /// the message containing the command, only if the arguments of the
+    /// command can be parsed as `A`; otherwise the message is passed to the
+    /// next matching handler.
+    pub fn command<S, A, F, Fut>(
+        &mut self,
+        dispatcher: &mut Dispatcher<S>,
+        name: &str,
+        description: &str,
+        handler: F,
+    ) -> &mut CommandEntry
+    where
+        S: Send + Sync + 'static,
+        A: FromArgs,
+        F: Fn(Context<S>, Message, A) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: IntoFlow,
+    {
+        let command_name = name.to_string();
+        dispatcher
+            .on_new_message(move |ctx, update: UpdateNewMessage| {
+                let args = message_command(&update.message).and_then(|c| A::from_args(c.args));
+                let future = match args {
+                    Some(args) => Some(handler(ctx, update.message, args)),
+                    None => {
+                        log::warn!("Invalid arguments for the command /{command_name}");
+                        None
+                    }
+                };
+                async move {
+                    match future {
+                        Some(future) => future.await.into_flow(),
+                        None => Flow::Continue,
+                    }
+                }
+            })
+            .filter(self.filter(name));
+
+        self.commands.push(CommandEntry {
+            name: name.into(),
+            descriptions: vec![(None, String::new(), description.into())],
+        });
+        self.commands.last_mut().unwrap()
+    }
+
+    /// Get a filter accepting the new messages containing the command
+    /// `name`, addressed to this bot or to no bot in particular. It can be
+    /// used with [`Handler::filter`](crate::dispatcher::Handler::filter) to
+    /// handle a command manually.
+    pub fn filter(&self, name: &str) -> impl Fn(&Update) -> bool + Send + Sync + 'static {
+        let name = name.to_string();
+        let bot_username = self.bot_username.clone();
+        move |update| {
+            let Update::NewMessage(update) = update else {
+                return false;
+            };
+            let Some(command) = message_command(&update.message) else {
+                return false;
+            };
+            if let (Some(to), Some(bot_username)) =
+                (command.bot_username, &*bot_username.read().unwrap())
+                && !to.eq_ignore_ascii_case(bot_username)
+            {
+                return false;
+            }
+            command.name.eq_ignore_ascii_case(&name)
+        }
+    }
+
+    /// Send the list of the registered commands to Telegram with
+    /// `setCommands`, once for every scope and language with a description.
+    pub async fn sync_commands(&self, client_id: i32) -> Result<(), Error> {
+        let mut groups: Vec<(Option<BotCommandScope>, String, Vec<BotCommand>)> = Vec::new();
+        for entry in &self.commands {
+            for (scope, language_code, description) in &entry.descriptions {
+                let command = BotCommand {
+                    command: entry.name.clone(),
+                    description: description.clone(),
+                };
+                match groups
+                    .iter_mut()
+                    .find(|(s, l, _)| s == scope && l == language_code)
+                {
+                    Some((_, _, commands)) => commands.push(command),
+                    None => groups.push((scope.clone(), language_code.clone(), vec![command])),
+                }
+            }
+        }
+
+        for (scope, language_code, commands) in groups {
+            functions::set_commands(scope, language_code, commands, client_id).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_command() {
+        assert_eq!(
+            parse("/start@my_bot  hello world"),
+            Some(Command {
+                name: "start",
+                bot_username: Some("my_bot"),
+                args: "hello world",
+            })
+        );
+        assert_eq!(
+            parse("/help"),
+            Some(Command {
+                name: "help",
+                bot_username: None,
+                args: "",
+            })
+        );
+        assert_eq!(parse("hello /start"), None);
+        assert_eq!(parse("/"), None);
+        assert_eq!(parse("/start@ arg"), None);
+    }
+
+    #[test]
+    fn from_args() {
+        assert_eq!(<(i32, i32)>::from_args("1 2"), Some((1, 2)));
+        assert_eq!(<(i32, i32)>::from_args("1"), None);
+        assert_eq!(<(i32, i32)>::from_args("1 2 3"), None);
+        assert_eq!(<(i32,)>::from_args("x"), None);
+        assert_eq!(<()>::from_args(""), Some(()));
+        assert_eq!(<()>::from_args("x"), None);
+        assert_eq!(
+            Vec::<String>::from_args(" a  b "),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+}
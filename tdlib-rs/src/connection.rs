@@ -0,0 +1,237 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The connection module contains a monitor of the connection state of every
+//! client, kept up to date from the `updateConnectionState` updates returned
+//! by [`receive`](crate::receive). A client is forgotten when it's closed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # async fn run(client_id: i32) {
+//! use tdlib_rs::connection;
+//!
+//! if connection::wait_until_ready(client_id).await {
+//!     let stats = connection::stats(client_id);
+//!     println!("Reconnected {} times", stats.reconnects);
+//! }
+//! # }
+//! ```
+use crate::enums::{AuthorizationState, ConnectionState, Update};
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+static MONITORS: Lazy<Mutex<HashMap<i32, Monitor>>> = Lazy::new(Default::default);
+/// The clients that were closed, which never become ready again. It's only
+/// locked while `MONITORS` is locked.
+static CLOSED: Lazy<Mutex<HashSet<i32>>> = Lazy::new(Default::default);
+
+/// The statistics about the connection state of a client.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// The time spent waiting for the network.
+    pub waiting_for_network: Duration,
+    /// The time spent connecting to a proxy server.
+    pub connecting_to_proxy: Duration,
+    /// The time spent connecting to the Telegram servers.
+    pub connecting: Duration,
+    /// The time spent downloading the data received while offline.
+    pub updating: Duration,
+    /// The time spent with a working connection.
+    pub ready: Duration,
+    /// The number of times the connection was lost after being ready.
+    pub reconnects: u32,
+}
+
+impl ConnectionStats {
+    fn duration_mut(&mut self, state: &ConnectionState) -> &mut Duration {
+        match state {
+            ConnectionState::WaitingForNetwork => &mut self.waiting_for_network,
+            ConnectionState::ConnectingToProxy => &mut self.connecting_to_proxy,
+            ConnectionState::Connecting => &mut self.connecting,
+            ConnectionState::Updating => &mut self.updating,
+            ConnectionState::Ready => &mut self.ready,
+        }
+    }
+}
+
+struct Monitor {
+    sender: watch::Sender<Option<ConnectionState>>,
+    since: Instant,
+    stats: ConnectionStats,
+}
+
+impl Monitor {
+    fn new() -> Self {
+        Self {
+            sender: watch::Sender::new(None),
+            since: Instant::now(),
+            stats: ConnectionStats::default(),
+        }
+    }
+
+    /// Get the statistics, including the time spent in the current state.
+    fn stats(&self) -> ConnectionStats {
+        let mut stats = self.stats.clone();
+        if let Some(state) = &*self.sender.borrow() {
+            *stats.duration_mut(state) += self.since.elapsed();
+        }
+        stats
+    }
+}
+
+/// Call `f` with the monitor of a client, creating it if needed.
+fn with_monitor<T>(client_id: i32, f: impl FnOnce(&mut Monitor) -> T) -> T {
+    let mut monitors = MONITORS.lock().unwrap();
+    f(monitors.entry(client_id).or_insert_with(Monitor::new))
+}
+
+/// Call `f` with the monitor of a client, if it exists.
+fn get_monitor<T>(client_id: i32, f: impl FnOnce(&Monitor) -> T) -> Option<T> {
+    MONITORS.lock().unwrap().get(&client_id).map(f)
+}
+
+/// Record the new connection state of a client, and forget the client when
+/// it's closed.
+pub(crate) fn notify(update: &Update, client_id: i32) {
+    let update = match update {
+        Update::ConnectionState(update) => update,
+        Update::AuthorizationState(update) => {
+            if update.authorization_state == AuthorizationState::Closed {
+                let mut monitors = MONITORS.lock().unwrap();
+                CLOSED.lock().unwrap().insert(client_id);
+                // Dropping the sender also stops the waiting receivers
+                monitors.remove(&client_id);
+            }
+            return;
+        }
+        // Unreachable if no other update is generated, with `TDLIB_RS_ALLOWLIST`
+        #[allow(unreachable_patterns)]
+        _ => return,
+    };
+    with_monitor(client_id, |monitor| {
+        let now = Instant::now();
+        let elapsed = now - monitor.since;
+        monitor.since = now;
+        let state = update.state.clone();
+        monitor.sender.send_modify(|current| {
+            if let Some(current) = current.as_ref() {
+                *monitor.stats.duration_mut(current) += elapsed;
+                if *current == ConnectionState::Ready && state != ConnectionState::Ready {
+                    monitor.stats.reconnects += 1;
+                }
+            }
+            *current = Some(state);
+        });
+    });
+}
+
+/// Get a receiver of the connection state of a client, or `None` if no
+/// `updateConnectionState` was received for the client, or if the client was
+/// closed. The receiver is closed when the client is closed.
+pub fn watch(client_id: i32) -> Option<watch::Receiver<Option<ConnectionState>>> {
+    get_monitor(client_id, |monitor| monitor.sender.subscribe())
+}
+
+/// Get the current connection state of a client, if known.
+pub fn state(client_id: i32) -> Option<ConnectionState> {
+    get_monitor(client_id, |monitor| monitor.sender.borrow().clone()).flatten()
+}
+
+/// Wait until the connection state of a client is
+/// [`ConnectionState::Ready`]. Returns `false` if the client is closed before
+/// being ready, immediately if it's already closed.
+pub async fn wait_until_ready(client_id: i32) -> bool {
+    // The client may not have received any update yet, so its monitor is
+    // created here, and removed when the client is closed
+    let mut receiver = {
+        let mut monitors = MONITORS.lock().unwrap();
+        if CLOSED.lock().unwrap().contains(&client_id) {
+            return false;
+        }
+        let monitor = monitors.entry(client_id).or_insert_with(Monitor::new);
+        monitor.sender.subscribe()
+    };
+    receiver
+        .wait_for(|state| *state == Some(ConnectionState::Ready))
+        .await
+        .is_ok()
+}
+
+/// Get the statistics about the connection state of a client, which are
+/// empty if the client is unknown or closed.
+pub fn stats(client_id: i32) -> ConnectionStats {
+    get_monitor(client_id, |monitor| monitor.stats()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{UpdateAuthorizationState, UpdateConnectionState};
+
+    fn update(state: ConnectionState) -> Update {
+        Update::ConnectionState(UpdateConnectionState { state })
+    }
+
+    #[test]
+    fn count_reconnects() {
+        let client_id = -1;
+        assert_eq!(state(client_id), None);
+        for state in [
+            ConnectionState::Connecting,
+            ConnectionState::Ready,
+            ConnectionState::WaitingForNetwork,
+            ConnectionState::Connecting,
+            ConnectionState::Updating,
+            ConnectionState::Ready,
+        ] {
+            notify(&update(state), client_id);
+        }
+        assert_eq!(state(client_id), Some(ConnectionState::Ready));
+        assert_eq!(stats(client_id).reconnects, 1);
+    }
+
+    #[test]
+    fn forget_closed_clients() {
+        let client_id = -2;
+        assert!(watch(client_id).is_none());
+        assert_eq!(stats(client_id), ConnectionStats::default());
+        // The getters don't register the client
+        assert!(!MONITORS.lock().unwrap().contains_key(&client_id));
+
+        notify(&update(ConnectionState::Connecting), client_id);
+        let receiver = watch(client_id).unwrap();
+        assert_eq!(*receiver.borrow(), Some(ConnectionState::Connecting));
+
+        let closed = Update::AuthorizationState(UpdateAuthorizationState {
+            authorization_state: AuthorizationState::Closed,
+        });
+        notify(&closed, client_id);
+        assert!(!MONITORS.lock().unwrap().contains_key(&client_id));
+        assert!(receiver.has_changed().is_err());
+        assert_eq!(state(client_id), None);
+    }
+
+    #[test]
+    fn wait_until_ready_or_closed() {
+        let client_id = -3;
+        notify(&update(ConnectionState::Ready), client_id);
+        assert!(futures_executor::block_on(wait_until_ready(client_id)));
+
+        let closed = Update::AuthorizationState(UpdateAuthorizationState {
+            authorization_state: AuthorizationState::Closed,
+        });
+        notify(&closed, client_id);
+        // It doesn't wait for a client that is already closed
+        assert!(!futures_executor::block_on(wait_until_ready(client_id)));
+        assert!(!MONITORS.lock().unwrap().contains_key(&client_id));
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The conversation module contains helpers to wait for future updates, such
+//! as the reply to a question, without blocking the main update loop.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # async fn run(client_id: i32, chat_id: i64, user_id: i64) {
+//! use std::time::Duration;
+//! use tdlib_rs::conversation::Conversation;
+//!
+//! let mut conversation = Conversation::new(client_id, chat_id, user_id);
+//! conversation.say("What's your name?").await.unwrap();
+//! let answer = match conversation.next_text(Duration::from_secs(60)).await {
+//!     Some(name) => format!("Hello {}!", name),
+//!     None => "Too late!".to_string(),
+//! };
+//! conversation.say(&answer).await.unwrap();
+//! # }
+//! ```
+use crate::dispatcher;
+use crate::enums::{self, InputMessageContent, Update};
+use crate::types::{Error, FormattedText, InputMessageText, Message};
+use crate::{functions, subscribe};
+use futures_channel::mpsc;
+use futures_timer::Delay;
+use futures_util::StreamExt;
+use futures_util::future::{self, Either};
+use std::future::Future;
+use std::pin::pin;
+use std::time::{Duration, Instant};
+
+/// Wait for `future` to complete, returning `None` if it doesn't complete
+/// within `duration`. Unlike `tokio::time::timeout`, it doesn't require a
+/// Tokio runtime.
+async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    match future::select(pin!(future), Delay::new(duration)).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+/// Wait for the next update of a client that satisfies `predicate`, returning
+/// `None` if no such update is received within `timeout`.
+///
+/// Only the updates received after this function is called are considered,
+/// so to wait for the effect of a request use [`subscribe`] before sending it.
+pub async fn wait_for<F>(client_id: i32, predicate: F, timeout: Duration) -> Option<Update>
+where
+    F: Fn(&Update) -> bool + Send + Sync + 'static,
+{
+    let mut updates = subscribe(client_id, predicate);
+    self::timeout(timeout, updates.next()).await.flatten()
+}
+
+/// A conversation with a user in a chat. The new messages sent by the user in
+/// the chat are queued from the creation of the conversation until it's
+/// dropped, so no reply is lost between two calls.
+pub struct Conversation {
+    client_id: i32,
+    chat_id: i64,
+    user_id: i64,
+    updates: mpsc::UnboundedReceiver<Update>,
+}
+
+impl Conversation {
+    /// Start a conversation with a user in a chat.
+    pub fn new(client_id: i32, chat_id: i64, user_id: i64) -> Self {
+        let updates = subscribe(client_id, move |update| {
+            matches!(update, Update::NewMessage(_))
+                && dispatcher::chat_id(update) == Some(chat_id)
+                && dispatcher::sender_id(update) == Some(user_id)
+        });
+        Self {
+            client_id,
+            chat_id,
+            user_id,
+            updates,
+        }
+    }
+
+    /// Get the identifier of the client.
+    pub fn client_id(&self) -> i32 {
+        self.client_id
+    }
+
+    /// Get the identifier of the chat.
+    pub fn chat_id(&self) -> i64 {
+        self.chat_id
+    }
+
+    /// Get the identifier of the user.
+    pub fn user_id(&self) -> i64 {
+        self.user_id
+    }
+
+    /// Wait for the next message of the user, returning `None` if no message
+    /// is received within `timeout`.
+    pub async fn next_message(&mut self, timeout: Duration) -> Option<Message> {
+        match self::timeout(timeout, self.updates.next()).await {
+            Some(Some(Update::NewMessage(update))) => Some(update.message),
+            _ => None,
+        }
+    }
+
+    /// Wait for the next text message of the user, returning `None` if no
+    /// message is received within `timeout`. Messages with a different
+    /// content are skipped.
+    pub async fn next_text(&mut self, timeout: Duration) -> Option<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let message = self.next_message(timeout).await?;
+            if let enums::MessageContent::MessageText(content) = message.content {
+                return Some(content.text.text);
+            }
+        }
+    }
+
+    /// Send a text message to the chat.
+    pub async fn say(&self, text: &str) -> Result<Message, Error> {
+        let content = InputMessageContent::InputMessageText(InputMessageText {
+            text: FormattedText {
+                text: text.into(),
+                entities: Vec::new(),
+            },
+            link_preview_options: None,
+            clear_draft: false,
+        });
+        // The `reply_markup` parameter is only available to bots
+        #[cfg(feature = "bots-only-api")]
+        let message = functions::send_message(
+            self.chat_id,
+            None,
+            None,
+            None,
+            None,
+            content,
+            self.client_id,
+        );
+        #[cfg(not(feature = "bots-only-api"))]
+        let message =
+            functions::send_message(self.chat_id, None, None, None, content, self.client_id);
+        let enums::Message::Message(message) = message.await?;
+        Ok(message)
+    }
+
+    /// Send a text message to the chat and wait for the reply of the user,
+    /// returning `None` if no message is received within `timeout`.
+    pub async fn ask(&mut self, text: &str, timeout: Duration) -> Result<Option<Message>, Error> {
+        self.say(text).await?;
+        Ok(self.next_message(timeout).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_without_runtime() {
+        let output = futures_executor::block_on(timeout(Duration::from_secs(10), async { 1 }));
+        assert_eq!(output, Some(1));
+
+        let output =
+            futures_executor::block_on(timeout(Duration::from_millis(10), future::pending::<()>()));
+        assert_eq!(output, None);
+    }
+}
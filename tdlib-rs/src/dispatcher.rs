@@ -0,0 +1,468 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The dispatcher module contains a registry of async handlers, each one
+//! called only for a certain kind of update and only if its filters match.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # async fn run(client_id: i32) {
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use tdlib_rs::dispatcher::{Context, Dispatcher};
+//! use tdlib_rs::types::UpdateNewMessage;
+//!
+//! let mut dispatcher = Dispatcher::new(AtomicUsize::new(0));
+//! dispatcher
+//!     .on_new_message(|ctx: Context<AtomicUsize>, update: UpdateNewMessage| async move {
+//!         ctx.state.fetch_add(1, Ordering::Relaxed);
+//!         println!("{:?}", update.message.content);
+//!     })
+//!     .text_matches(regex::Regex::new("^hello").unwrap());
+//!
+//! dispatcher.listen(client_id).await;
+//! # }
+//! ```
+use crate::enums::{MessageContent, MessageSender, Update};
+use crate::subscribe;
+use crate::types;
+use futures_util::StreamExt;
+use regex::Regex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+type BoxFuture = Pin<Box<dyn Future<Output = Flow> + Send>>;
+type Callback<S> = Box<dyn Fn(Context<S>, Update) -> Option<BoxFuture> + Send + Sync>;
+type Filter = Box<dyn Fn(&Update) -> bool + Send + Sync>;
+
+/// Whether the update must be passed to the next matching handlers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// Pass the update to the next matching handler.
+    Continue,
+    /// Stop handling the update.
+    Stop,
+}
+
+/// The value returned by a handler, converted to a [`Flow`]. Handlers
+/// returning `()` stop the handling of the update.
+pub trait IntoFlow {
+    fn into_flow(self) -> Flow;
+}
+
+impl IntoFlow for () {
+    fn into_flow(self) -> Flow {
+        Flow::Stop
+    }
+}
+
+impl IntoFlow for Flow {
+    fn into_flow(self) -> Flow {
+        self
+    }
+}
+
+/// The context passed to every handler.
+pub struct Context<S> {
+    /// The client that received the update.
+    pub client_id: i32,
+    /// The state shared between all the handlers.
+    pub state: Arc<S>,
+}
+
+impl<S> Clone for Context<S> {
+    fn clone(&self) -> Self {
+        Self {
+            client_id: self.client_id,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// A registered handler, which can be restricted with filters.
+pub struct Handler<S> {
+    callback: Callback<S>,
+    filters: Vec<Filter>,
+}
+
+impl<S> Handler<S> {
+    /// Call the handler only for the updates satisfying `filter`.
+    pub fn filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&Update) -> bool + Send + Sync + 'static,
+    {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Call the handler only for the updates related to the given chat.
+    /// See [`chat_id`] for the supported updates.
+    pub fn chat_id(&mut self, chat_id: i64) -> &mut Self {
+        self.filter(move |update| self::chat_id(update) == Some(chat_id))
+    }
+
+    /// Call the handler only for the updates sent by the given user or chat.
+    /// See [`sender_id`] for the supported updates.
+    pub fn sender_id(&mut self, sender_id: i64) -> &mut Self {
+        self.filter(move |update| self::sender_id(update) == Some(sender_id))
+    }
+
+    /// Call the handler only for the updates whose text matches `regex`.
+    /// See [`text`] for the supported updates.
+    pub fn text_matches(&mut self, regex: Regex) -> &mut Self {
+        self.filter(move |update| self::text(update).is_some_and(|text| regex.is_match(text)))
+    }
+
+    fn matches(&self, update: &Update) -> bool {
+        self.filters.iter().all(|filter| filter(update))
+    }
+}
+
+/// A registry of update handlers sharing a state of type `S`. Handlers are
+/// tried in the order in which they are registered.
+pub struct Dispatcher<S> {
+    state: Arc<S>,
+    handlers: Vec<Handler<S>>,
+}
+
+macro_rules! on_update {
+    ($(
+        $(#[$attr:meta])*
+        $name:ident => $variant:ident($ty:ident)
+    ),* $(,)?) => {$(
+        $(#[$attr])*
+        #[doc = concat!(
+            "Register a handler for the [`", stringify!($variant), "`](Update::", stringify!($variant),
+            ") updates."
+        )]
+        pub fn $name<F, Fut>(&mut self, handler: F) -> &mut Handler<S>
+        where
+            F: Fn(Context<S>, types::$ty) -> Fut + Send + Sync + 'static,
+            Fut: Future + Send + 'static,
+            Fut::Output: IntoFlow,
+        {
+            let handler = self.push(Box::new(move |ctx, update| match update {
+                Update::$variant(update) => {
+                    let future = handler(ctx, update);
+                    Some(Box::pin(async move { future.await.into_flow() }) as BoxFuture)
+                }
+                _ => None,
+            }));
+            // Check the kind of the update before any other filter, so that
+            // the update is cloned only for the handlers accepting it
+            handler.filter(|update| matches!(update, Update::$variant(_)))
+        }
+    )*};
+}
+
+impl<S: Send + Sync + 'static> Dispatcher<S> {
+    /// Create a dispatcher with no handlers.
+    pub fn new(state: S) -> Self {
+        Self {
+            state: Arc::new(state),
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Get the state shared between the handlers.
+    pub fn state(&self) -> &Arc<S> {
+        &self.state
+    }
+
+    fn push(&mut self, callback: Callback<S>) -> &mut Handler<S> {
+        self.handlers.push(Handler {
+            callback,
+            filters: Vec::new(),
+        });
+        self.handlers.last_mut().unwrap()
+    }
+
+    /// Register a handler for every kind of update. The typed `on_*` methods
+    /// only cover the most common kinds of update, so this is the way to
+    /// handle the other ones, matching the variant of the update.
+    pub fn on_update<F, Fut>(&mut self, handler: F) -> &mut Handler<S>
+    where
+        F: Fn(Context<S>, Update) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: IntoFlow,
+    {
+        self.push(Box::new(move |ctx, update| {
+            let future = handler(ctx, update);
+            Some(Box::pin(async move { future.await.into_flow() }) as BoxFuture)
+        }))
+    }
+
+    on_update! {
+        on_authorization_state => AuthorizationState(UpdateAuthorizationState),
+        on_new_message => NewMessage(UpdateNewMessage),
+        on_message_send_succeeded => MessageSendSucceeded(UpdateMessageSendSucceeded),
+        on_message_send_failed => MessageSendFailed(UpdateMessageSendFailed),
+        on_message_content => MessageContent(UpdateMessageContent),
+        on_message_edited => MessageEdited(UpdateMessageEdited),
+        on_delete_messages => DeleteMessages(UpdateDeleteMessages),
+        on_new_chat => NewChat(UpdateNewChat),
+        on_chat_title => ChatTitle(UpdateChatTitle),
+        on_chat_photo => ChatPhoto(UpdateChatPhoto),
+        on_chat_last_message => ChatLastMessage(UpdateChatLastMessage),
+        on_chat_position => ChatPosition(UpdateChatPosition),
+        on_chat_read_inbox => ChatReadInbox(UpdateChatReadInbox),
+        on_chat_read_outbox => ChatReadOutbox(UpdateChatReadOutbox),
+        on_chat_action => ChatAction(UpdateChatAction),
+        on_user => User(UpdateUser),
+        on_user_status => UserStatus(UpdateUserStatus),
+        on_basic_group => BasicGroup(UpdateBasicGroup),
+        on_supergroup => Supergroup(UpdateSupergroup),
+        on_file => File(UpdateFile),
+        on_option => Option(UpdateOption),
+        on_connection_state => ConnectionState(UpdateConnectionState),
+        #[cfg(feature = "bots-only-api")]
+        on_new_inline_query => NewInlineQuery(UpdateNewInlineQuery),
+        #[cfg(feature = "bots-only-api")]
+        on_new_callback_query => NewCallbackQuery(UpdateNewCallbackQuery),
+        #[cfg(feature = "bots-only-api")]
+        on_chat_member => ChatMember(UpdateChatMember),
+        #[cfg(feature = "bots-only-api")]
+        on_new_chat_join_request => NewChatJoinRequest(UpdateNewChatJoinRequest),
+    }
+
+    /// Pass an update to the matching handlers, in order, until one of them
+    /// returns [`Flow::Stop`]. Returns `true` if at least one handler was
+    /// called.
+    pub async fn dispatch(&self, update: Update, client_id: i32) -> bool {
+        let mut handled = false;
+        for handler in self.handlers.iter().filter(|h| h.matches(&update)) {
+            let ctx = Context {
+                client_id,
+                state: self.state.clone(),
+            };
+            let Some(future) = (handler.callback)(ctx, update.clone()) else {
+                continue;
+            };
+            handled = true;
+            if future.await == Flow::Stop {
+                break;
+            }
+        }
+        handled
+    }
+
+    /// Dispatch all the updates of a client, as they are received through
+    /// [`receive`](crate::receive). It never returns, so it's usually spawned
+    /// in a separate task.
+    pub async fn listen(&self, client_id: i32) {
+        let mut updates = subscribe(client_id, |_| true);
+        while let Some(update) = updates.next().await {
+            self.dispatch(update, client_id).await;
+        }
+    }
+}
+
+/// Get the identifier of the chat an update is related to. It supports the
+/// updates about a message, a chat, or a callback query.
+pub fn chat_id(update: &Update) -> Option<i64> {
+    Some(match update {
+        Update::NewMessage(update) => update.message.chat_id,
+        Update::MessageSendAcknowledged(update) => update.chat_id,
+        Update::MessageSendSucceeded(update) => update.message.chat_id,
+        Update::MessageSendFailed(update) => update.message.chat_id,
+        Update::MessageContent(update) => update.chat_id,
+        Update::MessageEdited(update) => update.chat_id,
+        Update::MessageIsPinned(update) => update.chat_id,
+        Update::MessageInteractionInfo(update) => update.chat_id,
+        Update::MessageContentOpened(update) => update.chat_id,
+        Update::MessageMentionRead(update) => update.chat_id,
+        Update::MessageUnreadReactions(update) => update.chat_id,
+        Update::DeleteMessages(update) => update.chat_id,
+        Update::NewChat(update) => update.chat.id,
+        Update::ChatTitle(update) => update.chat_id,
+        Update::ChatPhoto(update) => update.chat_id,
+        Update::ChatPermissions(update) => update.chat_id,
+        Update::ChatLastMessage(update) => update.chat_id,
+        Update::ChatPosition(update) => update.chat_id,
+        Update::ChatAddedToList(update) => update.chat_id,
+        Update::ChatRemovedFromList(update) => update.chat_id,
+        Update::ChatReadInbox(update) => update.chat_id,
+        Update::ChatReadOutbox(update) => update.chat_id,
+        Update::ChatDraftMessage(update) => update.chat_id,
+        Update::ChatNotificationSettings(update) => update.chat_id,
+        Update::ChatAction(update) => update.chat_id,
+        #[cfg(feature = "bots-only-api")]
+        Update::NewCallbackQuery(update) => update.chat_id,
+        #[cfg(feature = "bots-only-api")]
+        Update::ChatMember(update) => update.chat_id,
+        #[cfg(feature = "bots-only-api")]
+        Update::NewChatJoinRequest(update) => update.chat_id,
+        _ => return None,
+    })
+}
+
+/// Get the identifier of the user or chat that sent an update. It supports
+/// the updates about a new message, a chat action, or a query.
+pub fn sender_id(update: &Update) -> Option<i64> {
+    let sender = match update {
+        Update::NewMessage(update) => &update.message.sender_id,
+        Update::ChatAction(update) => &update.sender_id,
+        #[cfg(feature = "bots-only-api")]
+        Update::NewInlineQuery(update) => return Some(update.sender_user_id),
+        #[cfg(feature = "bots-only-api")]
+        Update::NewCallbackQuery(update) => return Some(update.sender_user_id),
+        _ => return None,
+    };
+    Some(match sender {
+        MessageSender::User(user) => user.user_id,
+        MessageSender::Chat(chat) => chat.chat_id,
+    })
+}
+
+/// Get the text of an update. It supports the updates about a new or edited
+/// text message, or an inline query.
+pub fn text(update: &Update) -> Option<&str> {
+    let content = match update {
+        Update::NewMessage(update) => &update.message.content,
+        Update::MessageContent(update) => &update.new_content,
+        #[cfg(feature = "bots-only-api")]
+        Update::NewInlineQuery(update) => return Some(&update.query),
+        _ => return None,
+    };
+    match content {
+        MessageContent::MessageText(text) => Some(&text.text.text),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::ChatAction;
+    use std::future::{Ready, ready};
+    use std::sync::Mutex;
+
+    type Log = Mutex<Vec<&'static str>>;
+
+    fn record(
+        name: &'static str,
+        flow: Flow,
+    ) -> impl Fn(Context<Log>, Update) -> Ready<Flow> + Send + Sync + 'static {
+        move |ctx, _| {
+            ctx.state.lock().unwrap().push(name);
+            ready(flow)
+        }
+    }
+
+    fn text_update(chat_id: i64, text: &str) -> Update {
+        Update::MessageContent(types::UpdateMessageContent {
+            chat_id,
+            message_id: 1,
+            new_content: MessageContent::MessageText(types::MessageText {
+                text: types::FormattedText {
+                    text: text.into(),
+                    entities: Vec::new(),
+                },
+                link_preview: None,
+                link_preview_options: None,
+            }),
+        })
+    }
+
+    fn action_update(chat_id: i64, user_id: i64) -> Update {
+        Update::ChatAction(types::UpdateChatAction {
+            chat_id,
+            topic_id: None,
+            sender_id: MessageSender::User(types::MessageSenderUser { user_id }),
+            action: ChatAction::Typing,
+        })
+    }
+
+    fn dispatch(dispatcher: &Dispatcher<Log>, update: Update) -> (bool, Vec<&'static str>) {
+        let handled = futures_executor::block_on(dispatcher.dispatch(update, 1));
+        (
+            handled,
+            std::mem::take(&mut dispatcher.state.lock().unwrap()),
+        )
+    }
+
+    #[test]
+    fn handlers_called_in_order() {
+        let mut dispatcher = Dispatcher::new(Log::default());
+        dispatcher.on_update(record("first", Flow::Continue));
+        dispatcher.on_update(record("second", Flow::Continue));
+        dispatcher.on_update(record("third", Flow::Continue));
+
+        let (handled, log) = dispatch(&dispatcher, text_update(1, "hello"));
+        assert!(handled);
+        assert_eq!(log, ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn stop_flow() {
+        let mut dispatcher = Dispatcher::new(Log::default());
+        dispatcher.on_update(record("continue", Flow::Continue));
+        dispatcher.on_update(|ctx: Context<Log>, _| async move {
+            ctx.state.lock().unwrap().push("unit");
+        });
+        dispatcher.on_update(record("stop", Flow::Stop));
+
+        let (_, log) = dispatch(&dispatcher, text_update(1, "hello"));
+        assert_eq!(log, ["continue", "unit"]);
+
+        let mut dispatcher = Dispatcher::new(Log::default());
+        dispatcher.on_update(record("stop", Flow::Stop));
+        dispatcher.on_update(record("skipped", Flow::Continue));
+
+        let (_, log) = dispatch(&dispatcher, text_update(1, "hello"));
+        assert_eq!(log, ["stop"]);
+    }
+
+    #[test]
+    fn filters() {
+        let mut dispatcher = Dispatcher::new(Log::default());
+        dispatcher
+            .on_update(record("chat", Flow::Continue))
+            .chat_id(1);
+        dispatcher
+            .on_update(record("sender", Flow::Continue))
+            .sender_id(7);
+        dispatcher
+            .on_update(record("text", Flow::Continue))
+            .text_matches(Regex::new("^hello").unwrap());
+        dispatcher
+            .on_update(record("both", Flow::Continue))
+            .chat_id(2)
+            .sender_id(7);
+
+        assert_eq!(
+            dispatch(&dispatcher, text_update(1, "hello world")),
+            (true, vec!["chat", "text"])
+        );
+        assert_eq!(
+            dispatch(&dispatcher, text_update(2, "hello")),
+            (true, vec!["text"])
+        );
+        assert_eq!(
+            dispatch(&dispatcher, action_update(2, 7)),
+            (true, vec!["sender", "both"])
+        );
+        assert_eq!(dispatch(&dispatcher, action_update(3, 8)), (false, vec![]));
+    }
+
+    #[test]
+    fn typed_handler_only_for_its_variant() {
+        let mut dispatcher = Dispatcher::new(Log::default());
+        dispatcher.on_message_content(|ctx: Context<Log>, _| async move {
+            ctx.state.lock().unwrap().push("content");
+            Flow::Continue
+        });
+
+        assert_eq!(
+            dispatch(&dispatcher, text_update(1, "hello")),
+            (true, vec!["content"])
+        );
+        assert_eq!(dispatch(&dispatcher, action_update(1, 7)), (false, vec![]));
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The error module contains a classification of the errors returned by
+//! TDLib, so that they can be handled without comparing their messages.
+//!
+//! # Examples
+//!
+//! ```
+//! use tdlib_rs::error::{ErrorKind, TdError};
+//! use tdlib_rs::types::Error;
+//!
+//! let error = TdError::from(Error {
+//!     code: 429,
+//!     message: "Too Many Requests: retry after 7".into(),
+//! });
+//! assert_eq!(error.kind(), &ErrorKind::FloodWait { retry_after: 7 });
+//! assert!(error.is_retryable());
+//! ```
+use crate::types::Error;
+use std::fmt;
+
+/// The kind of an error returned by TDLib.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Too many requests were sent, and the request can be retried after the
+    /// given number of seconds.
+    FloodWait {
+        /// The number of seconds to wait before retrying.
+        retry_after: u64,
+    },
+    /// The slow mode of the chat is enabled, and a message can be sent after
+    /// the given number of seconds.
+    SlowModeWait {
+        /// The number of seconds to wait before retrying.
+        retry_after: u64,
+    },
+    /// The authentication code is invalid.
+    PhoneCodeInvalid,
+    /// The authentication code has expired.
+    PhoneCodeExpired,
+    /// The phone number is invalid.
+    PhoneNumberInvalid,
+    /// The phone number is banned.
+    PhoneNumberBanned,
+    /// The password is invalid.
+    PasswordInvalid,
+    /// The account is protected by a password, which must be checked.
+    SessionPasswordNeeded,
+    /// The client is not authorized, or the session was terminated.
+    Unauthorized,
+    /// The chat was not found.
+    ChatNotFound,
+    /// The message was not found.
+    MessageNotFound,
+    /// The user was not found.
+    UserNotFound,
+    /// Another resource was not found.
+    NotFound,
+    /// The current user has no rights to perform the request.
+    Forbidden,
+    /// The request has invalid parameters.
+    BadRequest,
+    /// The request was aborted, for example because the client is closing.
+    Aborted,
+    /// The error must not be displayed to the user, as described by the
+    /// documentation of the error code 406.
+    Silent,
+    /// An internal error of TDLib or of the Telegram servers.
+    Internal,
+    /// Another error.
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify an error returned by TDLib.
+    pub fn of(error: &Error) -> Self {
+        let message = error.message.as_str();
+        if let Some(seconds) = wait_seconds(message, "FLOOD_WAIT_") {
+            return Self::FloodWait {
+                retry_after: seconds,
+            };
+        }
+        if let Some(seconds) = wait_seconds(message, "SLOWMODE_WAIT_") {
+            return Self::SlowModeWait {
+                retry_after: seconds,
+            };
+        }
+        match message {
+            "PHONE_CODE_INVALID" | "PHONE_CODE_EMPTY" => return Self::PhoneCodeInvalid,
+            "PHONE_CODE_EXPIRED" => return Self::PhoneCodeExpired,
+            "PHONE_NUMBER_INVALID" => return Self::PhoneNumberInvalid,
+            "PHONE_NUMBER_BANNED" => return Self::PhoneNumberBanned,
+            "PASSWORD_HASH_INVALID" => return Self::PasswordInvalid,
+            "SESSION_PASSWORD_NEEDED" => return Self::SessionPasswordNeeded,
+            "Chat not found" => return Self::ChatNotFound,
+            "Message not found" => return Self::MessageNotFound,
+            "User not found" => return Self::UserNotFound,
+            "Request aborted" => return Self::Aborted,
+            _ => {}
+        }
+        match error.code {
+            429 => Self::FloodWait {
+                retry_after: message
+                    .rsplit(' ')
+                    .next()
+                    .and_then(|seconds| seconds.parse().ok())
+                    .unwrap_or(1),
+            },
+            400 => Self::BadRequest,
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            406 => Self::Silent,
+            500 => Self::Internal,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Parse the number of seconds of a message such as `FLOOD_WAIT_7`, possibly
+/// preceded by other text.
+fn wait_seconds(message: &str, prefix: &str) -> Option<u64> {
+    let start = message.find(prefix)? + prefix.len();
+    let digits = message[start..]
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?;
+    digits.parse().ok()
+}
+
+/// An error returned by TDLib, together with its kind.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TdError {
+    kind: ErrorKind,
+    error: Error,
+}
+
+impl From<Error> for TdError {
+    fn from(error: Error) -> Self {
+        Self {
+            kind: ErrorKind::of(&error),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for TdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TDLib error {}: {}", self.error.code, self.error.message)
+    }
+}
+
+impl std::error::Error for TdError {}
+
+impl TdError {
+    /// Get the kind of the error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Get the original error.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Get the original error, consuming this one.
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+
+    /// Get the number of seconds to wait before retrying the request, if the
+    /// error is caused by a flood limit or by the slow mode.
+    pub fn retry_after(&self) -> Option<u64> {
+        match self.kind {
+            ErrorKind::FloodWait { retry_after } | ErrorKind::SlowModeWait { retry_after } => {
+                Some(retry_after)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the same request may succeed if retried later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::FloodWait { .. } | ErrorKind::SlowModeWait { .. } | ErrorKind::Internal
+        )
+    }
+
+    /// Whether the error is related to the authorization of the client.
+    pub fn is_auth(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::PhoneCodeInvalid
+                | ErrorKind::PhoneCodeExpired
+                | ErrorKind::PhoneNumberInvalid
+                | ErrorKind::PhoneNumberBanned
+                | ErrorKind::PasswordInvalid
+                | ErrorKind::SessionPasswordNeeded
+                | ErrorKind::Unauthorized
+        )
+    }
+
+    /// Whether the error is caused by an invalid request, which must be fixed
+    /// by the caller instead of being retried.
+    pub fn is_caller_bug(&self) -> bool {
+        matches!(self.kind, ErrorKind::BadRequest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(code: i32, message: &str) -> ErrorKind {
+        ErrorKind::of(&Error {
+            code,
+            message: message.into(),
+        })
+    }
+
+    #[test]
+    fn classify() {
+        assert_eq!(
+            kind(429, "Too Many Requests: retry after 30"),
+            ErrorKind::FloodWait { retry_after: 30 }
+        );
+        assert_eq!(
+            kind(420, "FLOOD_WAIT_12"),
+            ErrorKind::FloodWait { retry_after: 12 }
+        );
+        assert_eq!(
+            kind(400, "SLOWMODE_WAIT_5"),
+            ErrorKind::SlowModeWait { retry_after: 5 }
+        );
+        assert_eq!(kind(400, "PHONE_CODE_INVALID"), ErrorKind::PhoneCodeInvalid);
+        assert_eq!(kind(400, "Chat not found"), ErrorKind::ChatNotFound);
+        assert_eq!(kind(400, "Invalid chat identifier"), ErrorKind::BadRequest);
+        assert_eq!(kind(401, "Unauthorized"), ErrorKind::Unauthorized);
+        assert_eq!(kind(406, "Whatever"), ErrorKind::Silent);
+        assert_eq!(kind(-1, "Whatever"), ErrorKind::Other);
+    }
+
+    #[test]
+    fn flags() {
+        let error = TdError::from(Error {
+            code: 400,
+            message: "SESSION_PASSWORD_NEEDED".into(),
+        });
+        assert!(error.is_auth());
+        assert!(!error.is_retryable());
+        assert!(!error.is_caller_bug());
+        assert_eq!(error.error().message, "SESSION_PASSWORD_NEEDED");
+        assert_eq!(
+            error.to_string(),
+            "TDLib error 400: SESSION_PASSWORD_NEEDED"
+        );
+    }
+}
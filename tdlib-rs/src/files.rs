@@ -0,0 +1,347 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The files module contains helpers to download and upload files.
+//!
+//! TDLib returns immediately from `downloadFile` and `preliminaryUploadFile`,
+//! and notifies the progress of the transfer through `updateFile` updates.
+//! The functions of this module wait for those updates, so
+//! [`receive`](crate::receive) must be called while they are running.
+use crate::enums::{self, FileType, InputFile, Update};
+use crate::types::{self, File};
+use crate::{functions, subscribe};
+use futures_channel::oneshot;
+use futures_util::{Stream, StreamExt};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// The error type of a file transfer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransferError {
+    /// TDLib returned an error.
+    Request(types::Error),
+    /// The transfer was stopped before being completed, for example because
+    /// it was canceled. It contains the last known state of the file.
+    Stopped(File),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(error) => write!(
+                f,
+                "the request failed with TDLib error {}: {}",
+                error.code, error.message
+            ),
+            Self::Stopped(file) => write!(f, "the transfer of the file {} was stopped", file.id),
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+impl From<types::Error> for TransferError {
+    fn from(error: types::Error) -> Self {
+        Self::Request(error)
+    }
+}
+
+/// Get a stream of the new states of a file, received through `updateFile`.
+pub fn progress(file_id: i32, client_id: i32) -> impl Stream<Item = File> {
+    subscribe(
+        client_id,
+        move |update| matches!(update, Update::File(update) if update.file.id == file_id),
+    )
+    .filter_map(|update| async move { update.into_file().map(|update| update.file) })
+}
+
+/// Wait until the transfer of a file is completed or stopped.
+async fn wait_transfer(
+    mut file: File,
+    updates: impl Stream<Item = File>,
+    is_completed: fn(&File) -> bool,
+    is_active: fn(&File) -> bool,
+) -> Result<File, TransferError> {
+    let mut updates = std::pin::pin!(updates);
+    loop {
+        if is_completed(&file) {
+            return Ok(file);
+        }
+        if !is_active(&file) {
+            return Err(TransferError::Stopped(file));
+        }
+        match updates.next().await {
+            Some(new_file) => file = new_file,
+            None => return Err(TransferError::Stopped(file)),
+        }
+    }
+}
+
+/// Download a file, waiting until the download is completed.
+///
+/// # Arguments
+/// * `file_id` - Identifier of the file to download
+/// * `priority` - Priority of the download (1-32)
+/// * `client_id` - The client id to send the request to
+pub async fn download(file_id: i32, priority: i32, client_id: i32) -> Result<File, TransferError> {
+    // Subscribe before starting the download, to not lose any update
+    let updates = progress(file_id, client_id);
+    let enums::File::File(file) =
+        functions::download_file(file_id, priority, 0, 0, false, client_id).await?;
+
+    wait_transfer(
+        file,
+        updates,
+        |file| file.local.is_downloading_completed,
+        |file| file.local.is_downloading_active,
+    )
+    .await
+}
+
+/// Preliminarily upload a file, waiting until the upload is completed.
+///
+/// # Arguments
+/// * `file` - File to upload
+/// * `file_type` - File type; pass null if unknown
+/// * `priority` - Priority of the upload (1-32)
+/// * `client_id` - The client id to send the request to
+pub async fn upload(
+    file: InputFile,
+    file_type: Option<FileType>,
+    priority: i32,
+    client_id: i32,
+) -> Result<File, TransferError> {
+    // The file identifier is known only after the request, so all the file
+    // updates must be collected until then
+    let updates = subscribe(client_id, |update| matches!(update, Update::File(_)));
+    let enums::File::File(file) =
+        functions::preliminary_upload_file(file, file_type, priority, client_id).await?;
+
+    let file_id = file.id;
+    let updates = updates.filter_map(move |update| async move {
+        match update {
+            Update::File(update) if update.file.id == file_id => Some(update.file),
+            _ => None,
+        }
+    });
+
+    wait_transfer(
+        file,
+        updates,
+        |file| file.remote.is_uploading_completed,
+        |file| file.remote.is_uploading_active,
+    )
+    .await
+}
+
+/// A queue of downloads, which runs at most a fixed number of downloads at
+/// the same time. When a download ends, the waiting download with the
+/// highest priority is started.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run(client_id: i32) {
+/// use tdlib_rs::files::DownloadQueue;
+///
+/// let queue = DownloadQueue::new(2, client_id);
+/// let (first, second, third) = futures_util::join!(
+///     queue.download(1, 1),
+///     queue.download(2, 1),
+///     queue.download(3, 32),
+/// );
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DownloadQueue {
+    client_id: i32,
+    state: Arc<Mutex<QueueState>>,
+}
+
+struct QueueState {
+    limit: usize,
+    active: usize,
+    counter: u64,
+    waiting: BinaryHeap<Waiting>,
+}
+
+struct Waiting {
+    priority: i32,
+    counter: u64,
+    sender: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiting {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiting {}
+
+impl PartialOrd for Waiting {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiting {
+    /// Higher priorities first, then the oldest download first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.counter.cmp(&self.counter))
+    }
+}
+
+/// Release the slot of a download when dropped, even if the download future
+/// was dropped before its completion.
+struct Slot<'a>(&'a Mutex<QueueState>);
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        while let Some(waiting) = state.waiting.pop() {
+            // The slot is passed to the waiting download, if still alive
+            if waiting.sender.send(()).is_ok() {
+                return;
+            }
+        }
+        state.active -= 1;
+    }
+}
+
+/// A download waiting for a slot. If it's dropped after a slot was passed to
+/// it, the slot is passed to the next waiting download.
+struct Pending<'a> {
+    receiver: oneshot::Receiver<()>,
+    state: &'a Mutex<QueueState>,
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        // After closing the receiver no slot can be passed to it anymore, so
+        // only the slot that may have been passed before must be released
+        self.receiver.close();
+        if let Ok(Some(())) = self.receiver.try_recv() {
+            drop(Slot(self.state));
+        }
+    }
+}
+
+impl DownloadQueue {
+    /// Create a queue running at most `limit` downloads at the same time.
+    pub fn new(limit: usize, client_id: i32) -> Self {
+        Self {
+            client_id,
+            state: Arc::new(Mutex::new(QueueState {
+                limit: limit.max(1),
+                active: 0,
+                counter: 0,
+                waiting: BinaryHeap::new(),
+            })),
+        }
+    }
+
+    /// Get the number of downloads waiting to be started.
+    pub fn waiting(&self) -> usize {
+        self.state.lock().unwrap().waiting.len()
+    }
+
+    /// Get the number of downloads currently running.
+    pub fn active(&self) -> usize {
+        self.state.lock().unwrap().active
+    }
+
+    /// Download a file as soon as there is a free slot in the queue, waiting
+    /// until the download is completed. The priority is used both to sort the
+    /// queue and as the TDLib priority of the download.
+    pub async fn download(&self, file_id: i32, priority: i32) -> Result<File, TransferError> {
+        let _slot = self.acquire(priority).await;
+        download(file_id, priority, self.client_id).await
+    }
+
+    async fn acquire(&self, priority: i32) -> Slot<'_> {
+        let mut pending = {
+            let mut state = self.state.lock().unwrap();
+            if state.active < state.limit {
+                state.active += 1;
+                return Slot(&self.state);
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            state.counter += 1;
+            let counter = state.counter;
+            state.waiting.push(Waiting {
+                priority,
+                counter,
+                sender,
+            });
+            Pending {
+                receiver,
+                state: &self.state,
+            }
+        };
+
+        // The sender is never dropped without sending, since the state is
+        // owned by the queue that is borrowed here
+        (&mut pending.receiver).await.unwrap();
+        Slot(&self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+    use futures_util::task::noop_waker_ref;
+    use std::task::{Context, Poll};
+
+    #[test]
+    fn release_slot_of_canceled_download() {
+        let queue = DownloadQueue::new(1, 0);
+        let slot = queue.acquire(1).now_or_never().unwrap();
+        assert_eq!(queue.active(), 1);
+
+        // Start waiting for the slot, then drop the waiting download right
+        // after the slot was passed to it
+        let mut waiting = Box::pin(queue.acquire(1));
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(waiting.poll_unpin(&mut cx).is_pending());
+        assert_eq!(queue.waiting(), 1);
+        drop(slot);
+        assert_eq!(queue.waiting(), 0);
+        drop(waiting);
+
+        assert_eq!(queue.active(), 0);
+        let slot = queue.acquire(1).now_or_never();
+        assert!(slot.is_some());
+    }
+
+    #[test]
+    fn pass_slot_by_priority() {
+        let queue = DownloadQueue::new(1, 0);
+        let slot = queue.acquire(1).now_or_never().unwrap();
+
+        let mut low = Box::pin(queue.acquire(1));
+        let mut high = Box::pin(queue.acquire(32));
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(low.poll_unpin(&mut cx).is_pending());
+        assert!(high.poll_unpin(&mut cx).is_pending());
+
+        drop(slot);
+        assert!(low.poll_unpin(&mut cx).is_pending());
+        let Poll::Ready(slot) = high.poll_unpin(&mut cx) else {
+            panic!("the download with the highest priority must be started");
+        };
+        drop(slot);
+        assert!(low.poll_unpin(&mut cx).is_ready());
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::utf16_len;
+use crate::enums::TextEntityType;
+use crate::types::{
+    FormattedText, TextEntity, TextEntityTypeCustomEmoji, TextEntityTypeMediaTimestamp,
+    TextEntityTypeMentionName, TextEntityTypePreCode, TextEntityTypeTextUrl,
+};
+
+/// A builder of [`FormattedText`]s, computing the offsets and lengths of the
+/// entities in UTF-16 code units.
+///
+/// # Examples
+///
+/// ```
+/// use tdlib_rs::enums::TextEntityType;
+/// use tdlib_rs::formatting::FormattedTextBuilder;
+///
+/// let text = FormattedTextBuilder::new()
+///     .bold("Hello")
+///     .text(" 👋 ")
+///     .link("docs", "https://docs.rs/tdlib-rs")
+///     .wrap(TextEntityType::Italic, |b| b.text("see ").code("get_me"))
+///     .build();
+///
+/// assert_eq!(text.text, "Hello 👋 docssee get_me");
+/// assert_eq!(text.entities[1].offset, 9);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FormattedTextBuilder {
+    text: String,
+    length: i32,
+    entities: Vec<TextEntity>,
+}
+
+impl FormattedTextBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append plain text.
+    pub fn text(mut self, text: &str) -> Self {
+        self.text.push_str(text);
+        self.length += utf16_len(text);
+        self
+    }
+
+    /// Append text formatted with an entity of the given type.
+    pub fn entity(self, text: &str, r#type: TextEntityType) -> Self {
+        self.wrap(r#type, |builder| builder.text(text))
+    }
+
+    /// Append the text produced by `f`, covering it with an entity of the
+    /// given type. It can be used to nest entities.
+    pub fn wrap<F>(self, r#type: TextEntityType, f: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        let offset = self.length;
+        let mut builder = f(self);
+        let length = builder.length - offset;
+        if length > 0 {
+            builder.entities.push(TextEntity {
+                offset,
+                length,
+                r#type,
+            });
+        }
+        builder
+    }
+
+    /// Append a mention of a user, a supergroup, or a channel by their username.
+    pub fn mention(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Mention)
+    }
+
+    /// Append a hashtag text.
+    pub fn hashtag(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Hashtag)
+    }
+
+    /// Append a cashtag text.
+    pub fn cashtag(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Cashtag)
+    }
+
+    /// Append a bot command.
+    pub fn bot_command(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::BotCommand)
+    }
+
+    /// Append an HTTP URL.
+    pub fn url(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Url)
+    }
+
+    /// Append an email address.
+    pub fn email_address(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::EmailAddress)
+    }
+
+    /// Append a phone number.
+    pub fn phone_number(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::PhoneNumber)
+    }
+
+    /// Append a bank card number.
+    pub fn bank_card_number(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::BankCardNumber)
+    }
+
+    /// Append a bold text.
+    pub fn bold(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Bold)
+    }
+
+    /// Append an italic text.
+    pub fn italic(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Italic)
+    }
+
+    /// Append an underlined text.
+    pub fn underline(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Underline)
+    }
+
+    /// Append a strikethrough text.
+    pub fn strikethrough(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Strikethrough)
+    }
+
+    /// Append a spoiler text.
+    pub fn spoiler(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Spoiler)
+    }
+
+    /// Append an inline code text.
+    pub fn code(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Code)
+    }
+
+    /// Append a preformatted text.
+    pub fn pre(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::Pre)
+    }
+
+    /// Append a preformatted code text written in the given programming language.
+    pub fn pre_code(self, text: &str, language: &str) -> Self {
+        let r#type = TextEntityType::PreCode(TextEntityTypePreCode {
+            language: language.into(),
+        });
+        self.entity(text, r#type)
+    }
+
+    /// Append a block quote.
+    pub fn block_quote(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::BlockQuote)
+    }
+
+    /// Append a block quote collapsed by default.
+    pub fn expandable_block_quote(self, text: &str) -> Self {
+        self.entity(text, TextEntityType::ExpandableBlockQuote)
+    }
+
+    /// Append a text shown instead of a raw URL.
+    pub fn link(self, text: &str, url: &str) -> Self {
+        let r#type = TextEntityType::TextUrl(TextEntityTypeTextUrl { url: url.into() });
+        self.entity(text, r#type)
+    }
+
+    /// Append a text shown instead of a raw mention of the user.
+    pub fn mention_name(self, text: &str, user_id: i64) -> Self {
+        let r#type = TextEntityType::MentionName(TextEntityTypeMentionName { user_id });
+        self.entity(text, r#type)
+    }
+
+    /// Append a custom emoji, where `text` must be an emoji.
+    pub fn custom_emoji(self, text: &str, custom_emoji_id: i64) -> Self {
+        let r#type = TextEntityType::CustomEmoji(TextEntityTypeCustomEmoji { custom_emoji_id });
+        self.entity(text, r#type)
+    }
+
+    /// Append a media timestamp, in seconds.
+    pub fn media_timestamp(self, text: &str, media_timestamp: i32) -> Self {
+        let r#type =
+            TextEntityType::MediaTimestamp(TextEntityTypeMediaTimestamp { media_timestamp });
+        self.entity(text, r#type)
+    }
+
+    /// Build the [`FormattedText`], sorting the entities by offset and then
+    /// from the outermost to the innermost one.
+    pub fn build(mut self) -> FormattedText {
+        self.entities
+            .sort_by(|a, b| a.offset.cmp(&b.offset).then(b.length.cmp(&a.length)));
+        FormattedText {
+            text: self.text,
+            entities: self.entities,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_plain_text() {
+        let text = FormattedTextBuilder::new().text("a").text("b").build();
+        assert_eq!(text.text, "ab");
+        assert!(text.entities.is_empty());
+    }
+
+    #[test]
+    fn build_surrogate_pairs() {
+        let text = FormattedTextBuilder::new().text("😀😀").bold("è😀").build();
+        assert_eq!(
+            text.entities,
+            vec![TextEntity {
+                offset: 4,
+                length: 3,
+                r#type: TextEntityType::Bold,
+            }]
+        );
+    }
+
+    #[test]
+    fn build_nested_entities() {
+        let text = FormattedTextBuilder::new()
+            .text("> ")
+            .wrap(TextEntityType::Italic, |b| b.bold("ab").text("c"))
+            .build();
+        assert_eq!(text.text, "> abc");
+        assert_eq!(
+            text.entities,
+            vec![
+                TextEntity {
+                    offset: 2,
+                    length: 3,
+                    r#type: TextEntityType::Italic,
+                },
+                TextEntity {
+                    offset: 2,
+                    length: 2,
+                    r#type: TextEntityType::Bold,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn build_empty_entity() {
+        let text = FormattedTextBuilder::new().bold("").build();
+        assert!(text.entities.is_empty());
+    }
+}
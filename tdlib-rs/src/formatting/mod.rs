@@ -0,0 +1,184 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The formatting module contains helpers to build, inspect and render
+//! [`FormattedText`]s.
+//!
+//! The offsets and lengths of the [`TextEntity`]s are measured in UTF-16 code
+//! units, so they don't match the byte indexes of the Rust strings as soon as
+//! the text contains non-ASCII characters, and they count two units for each
+//! character outside of the Basic Multilingual Plane, such as most emoji.
+mod builder;
+mod render;
+
+pub use builder::FormattedTextBuilder;
+pub use render::{to_ansi, to_html, to_markdown};
+
+use crate::types::{FormattedText, TextEntity};
+
+/// Get the length of a string in UTF-16 code units.
+pub fn utf16_len(text: &str) -> i32 {
+    text.encode_utf16().count() as i32
+}
+
+/// Convert an offset in UTF-16 code units to a byte index of `text`.
+/// Returns `None` if the offset is out of bounds or if it splits a character.
+pub fn byte_index(text: &str, utf16_offset: i32) -> Option<usize> {
+    let utf16_offset = usize::try_from(utf16_offset).ok()?;
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units == utf16_offset {
+            return Some(index);
+        }
+        if units > utf16_offset {
+            return None;
+        }
+        units += c.len_utf16();
+    }
+    (units == utf16_offset).then_some(text.len())
+}
+
+/// Get the part of `text` covered by `entity`. Returns `None` if the entity
+/// is out of bounds or if it splits a character.
+pub fn entity_text<'a>(text: &'a FormattedText, entity: &TextEntity) -> Option<&'a str> {
+    let start = byte_index(&text.text, entity.offset)?;
+    let end = byte_index(&text.text, entity.offset.checked_add(entity.length)?)?;
+    text.text.get(start..end)
+}
+
+/// Iterate over the entities of `text` together with the part of the text
+/// they cover, skipping the invalid entities.
+pub fn entities_text(text: &FormattedText) -> impl Iterator<Item = (&TextEntity, &str)> {
+    text.entities
+        .iter()
+        .filter_map(|entity| Some((entity, entity_text(text, entity)?)))
+}
+
+/// Get the part of `text` starting at `offset` and long `length` UTF-16 code
+/// units, keeping the entities that intersect it, cut to its bounds. The
+/// entities out of the bounds of `text` are skipped. Returns `None` if the
+/// range is out of bounds or if it splits a character.
+pub fn slice(text: &FormattedText, offset: i32, length: i32) -> Option<FormattedText> {
+    let end = offset.checked_add(length)?;
+    let start_index = byte_index(&text.text, offset)?;
+    let end_index = byte_index(&text.text, end)?;
+    let text_len = utf16_len(&text.text);
+
+    let entities = text
+        .entities
+        .iter()
+        .filter_map(|entity| {
+            let entity_end = entity.offset.checked_add(entity.length)?;
+            if entity.offset < 0 || entity.length < 0 || entity_end > text_len {
+                return None;
+            }
+            let entity_start = entity.offset.max(offset);
+            let entity_end = entity_end.min(end);
+            (entity_start < entity_end).then(|| TextEntity {
+                offset: entity_start - offset,
+                length: entity_end - entity_start,
+                r#type: entity.r#type.clone(),
+            })
+        })
+        .collect();
+
+    Some(FormattedText {
+        text: text.text.get(start_index..end_index)?.to_string(),
+        entities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::TextEntityType;
+
+    fn entity(offset: i32, length: i32, r#type: TextEntityType) -> TextEntity {
+        TextEntity {
+            offset,
+            length,
+            r#type,
+        }
+    }
+
+    #[test]
+    fn check_utf16_len() {
+        assert_eq!(utf16_len("abc"), 3);
+        assert_eq!(utf16_len("è"), 1);
+        assert_eq!(utf16_len("😀"), 2);
+    }
+
+    #[test]
+    fn check_byte_index() {
+        let text = "a😀b";
+        assert_eq!(byte_index(text, 0), Some(0));
+        assert_eq!(byte_index(text, 1), Some(1));
+        assert_eq!(byte_index(text, 2), None);
+        assert_eq!(byte_index(text, 3), Some(5));
+        assert_eq!(byte_index(text, 4), Some(6));
+        assert_eq!(byte_index(text, 5), None);
+        assert_eq!(byte_index(text, -1), None);
+    }
+
+    #[test]
+    fn check_entity_text() {
+        let text = FormattedText {
+            text: "😀 bold".into(),
+            entities: vec![
+                entity(3, 4, TextEntityType::Bold),
+                entity(1, 2, TextEntityType::Italic),
+                entity(5, 10, TextEntityType::Code),
+            ],
+        };
+        assert_eq!(entity_text(&text, &text.entities[0]), Some("bold"));
+        assert_eq!(entity_text(&text, &text.entities[1]), None);
+        assert_eq!(entity_text(&text, &text.entities[2]), None);
+        assert_eq!(entities_text(&text).count(), 1);
+    }
+
+    #[test]
+    fn check_slice() {
+        let text = FormattedText {
+            text: "a 😀 bold".into(),
+            entities: vec![
+                entity(0, 1, TextEntityType::Italic),
+                entity(2, 7, TextEntityType::Bold),
+            ],
+        };
+        assert_eq!(
+            slice(&text, 2, 3),
+            Some(FormattedText {
+                text: "😀 ".into(),
+                entities: vec![entity(0, 3, TextEntityType::Bold)],
+            })
+        );
+        assert_eq!(slice(&text, 3, 2), None);
+        assert_eq!(slice(&text, 5, 10), None);
+        assert_eq!(slice(&text, 1, i32::MAX), None);
+    }
+
+    #[test]
+    fn check_slice_invalid_entities() {
+        let text = FormattedText {
+            text: "some bold".into(),
+            entities: vec![
+                entity(5, i32::MAX, TextEntityType::Bold),
+                entity(5, 10, TextEntityType::Italic),
+                entity(-2, 4, TextEntityType::Code),
+                entity(5, 4, TextEntityType::Underline),
+            ],
+        };
+        assert_eq!(
+            slice(&text, 3, 6),
+            Some(FormattedText {
+                text: "e bold".into(),
+                entities: vec![entity(2, 4, TextEntityType::Underline)],
+            })
+        );
+    }
+}
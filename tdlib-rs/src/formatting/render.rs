@@ -0,0 +1,571 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::byte_index;
+use crate::enums::TextEntityType;
+use crate::types::FormattedText;
+
+/// An entity converted to a range of bytes of the text.
+struct Span<'a> {
+    start: usize,
+    end: usize,
+    r#type: &'a TextEntityType,
+}
+
+/// A markup language in which a [`FormattedText`] can be rendered.
+trait Markup {
+    /// Write the opening tag of an entity.
+    fn open(&mut self, out: &mut String, r#type: &TextEntityType);
+
+    /// Write the closing tag of an entity.
+    fn close(&mut self, out: &mut String, r#type: &TextEntityType);
+
+    /// Write a part of the text, given the entities containing it, from the
+    /// outermost to the innermost one.
+    fn text(&mut self, out: &mut String, text: &str, active: &[&TextEntityType]);
+}
+
+/// Convert the valid entities of `text` to spans, sorted by start and then
+/// from the outermost to the innermost one.
+fn spans(text: &FormattedText) -> Vec<Span<'_>> {
+    let mut spans: Vec<Span> = text
+        .entities
+        .iter()
+        .filter_map(|entity| {
+            let start = byte_index(&text.text, entity.offset)?;
+            let end = byte_index(&text.text, entity.offset.checked_add(entity.length)?)?;
+            (start < end).then_some(Span {
+                start,
+                end,
+                r#type: &entity.r#type,
+            })
+        })
+        .collect();
+    spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+    spans
+}
+
+/// Render `text` with the given markup. Entities are written as properly
+/// nested tags: when an entity ends while an inner entity is still open, the
+/// inner entity is closed and then reopened after it.
+fn render<M: Markup>(text: &FormattedText, mut markup: M) -> String {
+    let spans = spans(text);
+    let mut boundaries: Vec<usize> = spans
+        .iter()
+        .flat_map(|span| [span.start, span.end])
+        .chain([0, text.text.len()])
+        .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut out = String::with_capacity(text.text.len());
+    let mut stack: Vec<&Span> = Vec::new();
+    let mut next_span = spans.iter().peekable();
+
+    for (i, &position) in boundaries.iter().enumerate() {
+        // Close the entities ending here, and the ones opened after them
+        if let Some(lowest) = stack.iter().position(|span| span.end == position) {
+            let reopen: Vec<&Span> = stack
+                .drain(lowest..)
+                .rev()
+                .inspect(|span| markup.close(&mut out, span.r#type))
+                .filter(|span| span.end != position)
+                .collect();
+            for span in reopen.into_iter().rev() {
+                markup.open(&mut out, span.r#type);
+                stack.push(span);
+            }
+        }
+
+        while let Some(span) = next_span.next_if(|span| span.start == position) {
+            markup.open(&mut out, span.r#type);
+            stack.push(span);
+        }
+
+        if let Some(&end) = boundaries.get(i + 1) {
+            let active: Vec<&TextEntityType> = stack.iter().map(|span| span.r#type).collect();
+            markup.text(&mut out, &text.text[position..end], &active);
+        }
+    }
+
+    out
+}
+
+/// Render a [`FormattedText`] to Telegram's MarkdownV2. Entities which are
+/// detected automatically by Telegram, such as URLs and hashtags, are written
+/// as plain text.
+pub fn to_markdown(text: &FormattedText) -> String {
+    render(text, MarkdownV2)
+}
+
+/// Render a [`FormattedText`] to Telegram's HTML. Entities which are detected
+/// automatically by Telegram, such as URLs and hashtags, are written as plain
+/// text.
+pub fn to_html(text: &FormattedText) -> String {
+    render(text, Html)
+}
+
+/// Render a [`FormattedText`] to a string styled with ANSI escape codes, to
+/// be printed on a terminal. The control characters of the text and of the
+/// URLs, such as ESC and BEL, are removed, so that a message can't inject its
+/// own escape sequences.
+pub fn to_ansi(text: &FormattedText) -> String {
+    render(text, Ansi::default())
+}
+
+struct MarkdownV2;
+
+impl MarkdownV2 {
+    fn is_code(r#type: &TextEntityType) -> bool {
+        matches!(
+            r#type,
+            TextEntityType::Code | TextEntityType::Pre | TextEntityType::PreCode(_)
+        )
+    }
+
+    fn is_quote(r#type: &TextEntityType) -> bool {
+        matches!(
+            r#type,
+            TextEntityType::BlockQuote | TextEntityType::ExpandableBlockQuote
+        )
+    }
+
+    /// Write the marker of an italic or underline entity. A `_` following
+    /// another one is separated by a `\r`, which is ignored by Telegram,
+    /// since the `__` of underline would be matched first otherwise.
+    fn push_underscores(out: &mut String, marker: &str) {
+        if out.ends_with('_') {
+            out.push('\r');
+        }
+        out.push_str(marker);
+    }
+
+    fn escape_code(out: &mut String, code: &str) {
+        for c in code.chars() {
+            if matches!(c, '`' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+
+    fn escape_url(out: &mut String, url: &str) {
+        for c in url.chars() {
+            if matches!(c, ')' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+}
+
+impl Markup for MarkdownV2 {
+    fn open(&mut self, out: &mut String, r#type: &TextEntityType) {
+        match r#type {
+            TextEntityType::Bold => out.push('*'),
+            TextEntityType::Italic => Self::push_underscores(out, "_"),
+            TextEntityType::Underline => Self::push_underscores(out, "__"),
+            TextEntityType::Strikethrough => out.push('~'),
+            TextEntityType::Spoiler => out.push_str("||"),
+            TextEntityType::Code => out.push('`'),
+            TextEntityType::Pre => out.push_str("```\n"),
+            TextEntityType::PreCode(pre) => {
+                out.push_str("```");
+                Self::escape_code(out, &pre.language);
+                out.push('\n');
+            }
+            TextEntityType::BlockQuote => out.push('>'),
+            TextEntityType::ExpandableBlockQuote => out.push_str("**>"),
+            TextEntityType::TextUrl(_) | TextEntityType::MentionName(_) => out.push('['),
+            TextEntityType::CustomEmoji(_) => out.push_str("!["),
+            _ => (),
+        }
+    }
+
+    fn close(&mut self, out: &mut String, r#type: &TextEntityType) {
+        match r#type {
+            TextEntityType::Bold => out.push('*'),
+            TextEntityType::Italic => Self::push_underscores(out, "_"),
+            TextEntityType::Underline => Self::push_underscores(out, "__"),
+            TextEntityType::Strikethrough => out.push('~'),
+            TextEntityType::Spoiler => out.push_str("||"),
+            TextEntityType::Code => out.push('`'),
+            TextEntityType::Pre | TextEntityType::PreCode(_) => out.push_str("\n```"),
+            TextEntityType::ExpandableBlockQuote => out.push_str("||"),
+            TextEntityType::TextUrl(url) => {
+                out.push_str("](");
+                Self::escape_url(out, &url.url);
+                out.push(')');
+            }
+            TextEntityType::MentionName(mention) => {
+                out.push_str(&format!("](tg://user?id={})", mention.user_id));
+            }
+            TextEntityType::CustomEmoji(emoji) => {
+                out.push_str(&format!("](tg://emoji?id={})", emoji.custom_emoji_id));
+            }
+            _ => (),
+        }
+    }
+
+    fn text(&mut self, out: &mut String, text: &str, active: &[&TextEntityType]) {
+        let is_code = active.iter().any(|r#type| Self::is_code(r#type));
+        let is_quote = active.iter().any(|r#type| Self::is_quote(r#type));
+        for c in text.chars() {
+            let escape = if is_code {
+                matches!(c, '`' | '\\')
+            } else {
+                matches!(
+                    c,
+                    '_' | '*'
+                        | '['
+                        | ']'
+                        | '('
+                        | ')'
+                        | '~'
+                        | '`'
+                        | '>'
+                        | '#'
+                        | '+'
+                        | '-'
+                        | '='
+                        | '|'
+                        | '{'
+                        | '}'
+                        | '.'
+                        | '!'
+                        | '\\'
+                )
+            };
+            if escape {
+                out.push('\\');
+            }
+            out.push(c);
+            if c == '\n' && is_quote {
+                out.push('>');
+            }
+        }
+    }
+}
+
+struct Html;
+
+impl Html {
+    fn escape(out: &mut String, text: &str) {
+        for c in text.chars() {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                c => out.push(c),
+            }
+        }
+    }
+}
+
+impl Markup for Html {
+    fn open(&mut self, out: &mut String, r#type: &TextEntityType) {
+        match r#type {
+            TextEntityType::Bold => out.push_str("<b>"),
+            TextEntityType::Italic => out.push_str("<i>"),
+            TextEntityType::Underline => out.push_str("<u>"),
+            TextEntityType::Strikethrough => out.push_str("<s>"),
+            TextEntityType::Spoiler => out.push_str("<tg-spoiler>"),
+            TextEntityType::Code => out.push_str("<code>"),
+            TextEntityType::Pre => out.push_str("<pre>"),
+            TextEntityType::PreCode(pre) => {
+                out.push_str("<pre><code class=\"language-");
+                Self::escape(out, &pre.language);
+                out.push_str("\">");
+            }
+            TextEntityType::BlockQuote => out.push_str("<blockquote>"),
+            TextEntityType::ExpandableBlockQuote => out.push_str("<blockquote expandable>"),
+            TextEntityType::TextUrl(url) => {
+                out.push_str("<a href=\"");
+                Self::escape(out, &url.url);
+                out.push_str("\">");
+            }
+            TextEntityType::MentionName(mention) => {
+                out.push_str(&format!("<a href=\"tg://user?id={}\">", mention.user_id));
+            }
+            TextEntityType::CustomEmoji(emoji) => {
+                out.push_str(&format!(
+                    "<tg-emoji emoji-id=\"{}\">",
+                    emoji.custom_emoji_id
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    fn close(&mut self, out: &mut String, r#type: &TextEntityType) {
+        out.push_str(match r#type {
+            TextEntityType::Bold => "</b>",
+            TextEntityType::Italic => "</i>",
+            TextEntityType::Underline => "</u>",
+            TextEntityType::Strikethrough => "</s>",
+            TextEntityType::Spoiler => "</tg-spoiler>",
+            TextEntityType::Code => "</code>",
+            TextEntityType::Pre => "</pre>",
+            TextEntityType::PreCode(_) => "</code></pre>",
+            TextEntityType::BlockQuote | TextEntityType::ExpandableBlockQuote => "</blockquote>",
+            TextEntityType::TextUrl(_) | TextEntityType::MentionName(_) => "</a>",
+            TextEntityType::CustomEmoji(_) => "</tg-emoji>",
+            _ => "",
+        });
+    }
+
+    fn text(&mut self, out: &mut String, text: &str, _active: &[&TextEntityType]) {
+        Self::escape(out, text);
+    }
+}
+
+/// The ANSI renderer writes the full style of every part of the text, since
+/// the escape codes can't be nested.
+#[derive(Default)]
+struct Ansi {
+    style: String,
+}
+
+impl Ansi {
+    const RESET: &'static str = "\x1b[0m";
+    const QUOTE: &'static str = "│ ";
+
+    fn codes(r#type: &TextEntityType) -> &'static [u8] {
+        match r#type {
+            TextEntityType::Bold => &[1],
+            TextEntityType::Italic => &[3],
+            TextEntityType::Underline => &[4],
+            TextEntityType::Strikethrough => &[9],
+            TextEntityType::Spoiler => &[2],
+            TextEntityType::Code | TextEntityType::Pre | TextEntityType::PreCode(_) => &[36],
+            TextEntityType::BlockQuote | TextEntityType::ExpandableBlockQuote => &[3],
+            TextEntityType::Mention
+            | TextEntityType::Hashtag
+            | TextEntityType::Cashtag
+            | TextEntityType::BotCommand
+            | TextEntityType::Url
+            | TextEntityType::EmailAddress
+            | TextEntityType::PhoneNumber
+            | TextEntityType::BankCardNumber
+            | TextEntityType::TextUrl(_)
+            | TextEntityType::MentionName(_)
+            | TextEntityType::MediaTimestamp(_) => &[34, 4],
+            TextEntityType::CustomEmoji(_) => &[],
+        }
+    }
+
+    fn is_quote(r#type: &TextEntityType) -> bool {
+        MarkdownV2::is_quote(r#type)
+    }
+
+    /// Write `text` without the C0 and C1 control characters, except the
+    /// line feeds and the tabs if `keep_whitespace` is set.
+    fn strip_controls(out: &mut String, text: &str, keep_whitespace: bool) {
+        out.extend(
+            text.chars()
+                .filter(|&c| !c.is_control() || (keep_whitespace && matches!(c, '\n' | '\t'))),
+        );
+    }
+}
+
+impl Markup for Ansi {
+    fn open(&mut self, out: &mut String, r#type: &TextEntityType) {
+        match r#type {
+            TextEntityType::TextUrl(url) => {
+                // OSC 8 hyperlink
+                out.push_str("\x1b]8;;");
+                Self::strip_controls(out, &url.url, false);
+                out.push_str("\x1b\\");
+            }
+            r#type if Self::is_quote(r#type) => out.push_str(Self::QUOTE),
+            _ => (),
+        }
+    }
+
+    fn close(&mut self, out: &mut String, r#type: &TextEntityType) {
+        if let TextEntityType::TextUrl(_) = r#type {
+            out.push_str("\x1b]8;;\x1b\\");
+        }
+        if !self.style.is_empty() {
+            out.push_str(Self::RESET);
+            self.style.clear();
+        }
+    }
+
+    fn text(&mut self, out: &mut String, text: &str, active: &[&TextEntityType]) {
+        let codes: Vec<String> = active
+            .iter()
+            .flat_map(|r#type| Self::codes(r#type))
+            .map(|code| code.to_string())
+            .collect();
+        let style = if codes.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", codes.join(";"))
+        };
+        if style != self.style {
+            if !self.style.is_empty() {
+                out.push_str(Self::RESET);
+            }
+            out.push_str(&style);
+            self.style = style;
+        }
+
+        if active.iter().any(|r#type| Self::is_quote(r#type)) {
+            let text = text.replace('\n', &format!("\n{}", Self::QUOTE));
+            Self::strip_controls(out, &text, true);
+        } else {
+            Self::strip_controls(out, text, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TextEntity, TextEntityTypePreCode, TextEntityTypeTextUrl};
+
+    fn text(text: &str, entities: &[(i32, i32, TextEntityType)]) -> FormattedText {
+        FormattedText {
+            text: text.into(),
+            entities: entities
+                .iter()
+                .map(|(offset, length, r#type)| TextEntity {
+                    offset: *offset,
+                    length: *length,
+                    r#type: r#type.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn link(url: &str) -> TextEntityType {
+        TextEntityType::TextUrl(TextEntityTypeTextUrl { url: url.into() })
+    }
+
+    #[test]
+    fn render_plain_text() {
+        let text = text("a < b.", &[]);
+        assert_eq!(to_html(&text), "a &lt; b.");
+        assert_eq!(to_markdown(&text), "a < b\\.");
+        assert_eq!(to_ansi(&text), "a < b.");
+    }
+
+    #[test]
+    fn render_surrogate_pairs() {
+        // "😀" is 2 UTF-16 code units long
+        let text = text(
+            "😀 bold 😀",
+            &[(3, 4, TextEntityType::Bold), (8, 2, TextEntityType::Italic)],
+        );
+        assert_eq!(to_html(&text), "😀 <b>bold</b> <i>😀</i>");
+        assert_eq!(to_markdown(&text), "😀 *bold* _😀_");
+        assert_eq!(to_ansi(&text), "😀 \x1b[1mbold\x1b[0m \x1b[3m😀\x1b[0m");
+    }
+
+    #[test]
+    fn render_split_surrogate_pair() {
+        // Entities splitting a character are invalid and ignored
+        let text = text("😀😀", &[(1, 2, TextEntityType::Bold)]);
+        assert_eq!(to_html(&text), "😀😀");
+    }
+
+    #[test]
+    fn render_nested_entities() {
+        let text = text(
+            "bold italic",
+            &[
+                (0, 11, TextEntityType::Bold),
+                (5, 6, TextEntityType::Italic),
+            ],
+        );
+        assert_eq!(to_html(&text), "<b>bold <i>italic</i></b>");
+        assert_eq!(to_markdown(&text), "*bold _italic_*");
+        assert_eq!(to_ansi(&text), "\x1b[1mbold \x1b[0m\x1b[1;3mitalic\x1b[0m");
+    }
+
+    #[test]
+    fn render_overlapping_entities() {
+        let text = text(
+            "abcd",
+            &[(0, 3, TextEntityType::Bold), (1, 3, TextEntityType::Italic)],
+        );
+        assert_eq!(to_html(&text), "<b>a<i>bc</i></b><i>d</i>");
+        assert_eq!(to_markdown(&text), "*a_bc_*_d_");
+    }
+
+    #[test]
+    fn render_links() {
+        let text = text("a link", &[(2, 4, link("https://x.y/(z)"))]);
+        assert_eq!(to_html(&text), "a <a href=\"https://x.y/(z)\">link</a>");
+        assert_eq!(to_markdown(&text), "a [link](https://x.y/(z\\))");
+    }
+
+    #[test]
+    fn strip_ansi_control_characters() {
+        let text = text(
+            "a\x1b[2Jb\x07\u{9b}c\nd link",
+            &[(12, 4, link("https://x.y/\x1b\\\x1b]0;title\x07z"))],
+        );
+        assert_eq!(
+            to_ansi(&text),
+            "a[2Jbc\nd \x1b]8;;https://x.y/\\]0;titlez\x1b\\\x1b[34;4mlink\x1b]8;;\x1b\\\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn render_code() {
+        let pre = TextEntityType::PreCode(TextEntityTypePreCode {
+            language: "rust".into(),
+        });
+        let text = text("let a = b.c;", &[(0, 12, pre)]);
+        assert_eq!(
+            to_html(&text),
+            "<pre><code class=\"language-rust\">let a = b.c;</code></pre>"
+        );
+        assert_eq!(to_markdown(&text), "```rust\nlet a = b.c;\n```");
+    }
+
+    #[test]
+    fn render_code_language() {
+        let pre = TextEntityType::PreCode(TextEntityTypePreCode {
+            language: "a`b\\".into(),
+        });
+        let text = text("code", &[(0, 4, pre)]);
+        assert_eq!(to_markdown(&text), "```a\\`b\\\\\ncode\n```");
+    }
+
+    #[test]
+    fn render_italic_and_underline() {
+        let adjacent = text(
+            "ab",
+            &[
+                (0, 1, TextEntityType::Italic),
+                (1, 1, TextEntityType::Underline),
+            ],
+        );
+        assert_eq!(to_markdown(&adjacent), "_a_\r__b__");
+
+        let nested = text(
+            "ab",
+            &[
+                (0, 2, TextEntityType::Underline),
+                (0, 2, TextEntityType::Italic),
+            ],
+        );
+        assert_eq!(to_html(&nested), "<u><i>ab</i></u>");
+        assert_eq!(to_markdown(&nested), "__\r_ab_\r__");
+    }
+
+    #[test]
+    fn render_block_quote() {
+        let text = text("a\nb", &[(0, 3, TextEntityType::BlockQuote)]);
+        assert_eq!(to_html(&text), "<blockquote>a\nb</blockquote>");
+        assert_eq!(to_markdown(&text), ">a\n>b");
+    }
+}
@@ -7,4 +7,10 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+include!(concat!(env!("OUT_DIR"), "/generated/mod.rs"));
+
+/// The updates generated when `TDLIB_RS_ALLOWLIST` is set.
+#[cfg(tdlib_rs_allowlist)]
+pub(crate) mod allowlist {
+    include!(concat!(env!("OUT_DIR"), "/allowlist.rs"));
+}
@@ -0,0 +1,344 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The keyboard module contains a builder of inline keyboards and, with the
+//! `bots-only-api` and `dispatcher` features, the handlers of the callback
+//! queries sent by their buttons, registered with
+//! `Dispatcher::on_callback_payload`.
+//!
+//! Typed payloads implement [`CallbackData`] and are encoded in the `data` of
+//! the buttons as their prefix followed by their JSON representation, which
+//! must fit in [`MAX_CALLBACK_DATA_LEN`] bytes.
+use crate::enums::{ButtonStyle, InlineKeyboardButtonType, ReplyMarkup};
+use crate::types::{
+    InlineKeyboardButton, InlineKeyboardButtonTypeCallback, InlineKeyboardButtonTypeUrl,
+    ReplyMarkupInlineKeyboard,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// The maximum length in bytes of the data of a callback button.
+pub const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+/// A payload that can be attached to a callback button.
+pub trait CallbackData: Serialize + DeserializeOwned {
+    /// The prefix identifying the payload type. It must not contain `:`.
+    const PREFIX: &'static str;
+}
+
+/// An error returned when a payload can't be encoded.
+#[derive(Debug)]
+pub enum CallbackDataError {
+    /// The payload can't be serialized.
+    Serialize(serde_json::Error),
+    /// The encoded payload is longer than [`MAX_CALLBACK_DATA_LEN`] bytes. It
+    /// contains the encoded length.
+    TooLong(usize),
+}
+
+impl fmt::Display for CallbackDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize(error) => write!(f, "the payload can't be serialized: {}", error),
+            Self::TooLong(len) => write!(
+                f,
+                "the encoded payload is {} bytes long, the limit is {}",
+                len, MAX_CALLBACK_DATA_LEN
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CallbackDataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialize(error) => Some(error),
+            Self::TooLong(_) => None,
+        }
+    }
+}
+
+/// Encode a payload as the data of a callback button.
+pub fn encode<P: CallbackData>(payload: &P) -> Result<Vec<u8>, CallbackDataError> {
+    let mut data = format!("{}:", P::PREFIX).into_bytes();
+    serde_json::to_writer(&mut data, payload).map_err(CallbackDataError::Serialize)?;
+    if data.len() > MAX_CALLBACK_DATA_LEN {
+        return Err(CallbackDataError::TooLong(data.len()));
+    }
+    Ok(data)
+}
+
+/// Decode a payload from the data of a callback button, returning `None` if
+/// the data doesn't contain a payload of type `P`.
+pub fn decode<P: CallbackData>(data: &[u8]) -> Option<P> {
+    let json = data
+        .strip_prefix(P::PREFIX.as_bytes())?
+        .strip_prefix(b":")?;
+    serde_json::from_slice(json).ok()
+}
+
+/// A builder of inline keyboards. Buttons are added to the last row, and new
+/// rows are started with [`InlineKeyboardBuilder::row`].
+#[derive(Clone, Debug, Default)]
+pub struct InlineKeyboardBuilder {
+    rows: Vec<Vec<InlineKeyboardButton>>,
+}
+
+impl InlineKeyboardBuilder {
+    /// Create an empty keyboard.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new row of buttons.
+    pub fn row(mut self) -> Self {
+        self.rows.push(Vec::new());
+        self
+    }
+
+    /// Add a button to the last row.
+    pub fn button(mut self, button: InlineKeyboardButton) -> Self {
+        match self.rows.last_mut() {
+            Some(row) => row.push(button),
+            None => self.rows.push(vec![button]),
+        }
+        self
+    }
+
+    /// Add a button of the given type with the default style.
+    pub fn typed(self, text: &str, r#type: InlineKeyboardButtonType) -> Self {
+        self.button(InlineKeyboardButton {
+            text: text.into(),
+            icon_custom_emoji_id: 0,
+            style: ButtonStyle::Default,
+            r#type,
+        })
+    }
+
+    /// Add a button opening a URL.
+    pub fn url(self, text: &str, url: &str) -> Self {
+        self.typed(
+            text,
+            InlineKeyboardButtonType::Url(InlineKeyboardButtonTypeUrl { url: url.into() }),
+        )
+    }
+
+    /// Add a button sending a callback query with raw data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is longer than [`MAX_CALLBACK_DATA_LEN`] bytes.
+    pub fn callback(self, text: &str, data: &[u8]) -> Self {
+        assert!(
+            data.len() <= MAX_CALLBACK_DATA_LEN,
+            "callback data is {} bytes long, the limit is {}",
+            data.len(),
+            MAX_CALLBACK_DATA_LEN
+        );
+        self.typed(
+            text,
+            InlineKeyboardButtonType::Callback(InlineKeyboardButtonTypeCallback {
+                data: STANDARD.encode(data),
+            }),
+        )
+    }
+
+    /// Add a button sending a callback query with a typed payload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the payload can't be encoded, see [`encode`].
+    pub fn payload<P: CallbackData>(self, text: &str, payload: &P) -> Self {
+        match encode(payload) {
+            Ok(data) => self.callback(text, &data),
+            Err(error) => panic!("invalid callback payload: {}", error),
+        }
+    }
+
+    /// Build the keyboard, skipping empty rows.
+    pub fn build(mut self) -> ReplyMarkup {
+        self.rows.retain(|row| !row.is_empty());
+        ReplyMarkup::InlineKeyboard(ReplyMarkupInlineKeyboard { rows: self.rows })
+    }
+}
+
+#[cfg(all(feature = "bots-only-api", feature = "dispatcher"))]
+pub use router::CallbackAnswer;
+
+#[cfg(all(feature = "bots-only-api", feature = "dispatcher"))]
+mod router {
+    use super::{CallbackData, STANDARD, decode};
+    use crate::dispatcher::{Context, Dispatcher, Flow, Handler};
+    use crate::enums::{CallbackQueryPayload, Update};
+    use crate::functions;
+    use crate::types::UpdateNewCallbackQuery;
+    use base64::Engine;
+    use std::future::Future;
+
+    /// The answer sent with `answerCallbackQuery` after a handler is called.
+    #[derive(Clone, Debug, Default)]
+    pub struct CallbackAnswer {
+        /// The text of the answer.
+        pub text: String,
+        /// Pass true to show an alert instead of a toast notification.
+        pub show_alert: bool,
+        /// The URL to be opened.
+        pub url: String,
+        /// The time during which the result of the query can be cached, in
+        /// seconds.
+        pub cache_time: i32,
+    }
+
+    impl From<()> for CallbackAnswer {
+        fn from(_: ()) -> Self {
+            Self::default()
+        }
+    }
+
+    impl From<String> for CallbackAnswer {
+        fn from(text: String) -> Self {
+            Self {
+                text,
+                ..Default::default()
+            }
+        }
+    }
+
+    impl From<&str> for CallbackAnswer {
+        fn from(text: &str) -> Self {
+            text.to_string().into()
+        }
+    }
+
+    /// Decode the payload of type `P` of a callback query, if any.
+    fn payload<P: CallbackData>(query: &UpdateNewCallbackQuery) -> Option<P> {
+        let CallbackQueryPayload::Data(payload) = &query.payload else {
+            return None;
+        };
+        decode(&STANDARD.decode(&payload.data).ok()?)
+    }
+
+    impl<S: Send + Sync + 'static> Dispatcher<S> {
+        /// Register a handler for the callback queries with a payload of type
+        /// `P`, sent by the buttons of an
+        /// [`InlineKeyboardBuilder`](super::InlineKeyboardBuilder). The value
+        /// returned by the handler is sent as the answer to the query.
+        pub fn on_callback_payload<P, F, Fut, A>(&mut self, handler: F) -> &mut Handler<S>
+        where
+            P: CallbackData + 'static,
+            F: Fn(Context<S>, UpdateNewCallbackQuery, P) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = A> + Send + 'static,
+            A: Into<CallbackAnswer>,
+        {
+            self.on_new_callback_query(move |ctx, query| {
+                let (client_id, query_id) = (ctx.client_id, query.id);
+                let future = payload::<P>(&query).map(|payload| handler(ctx, query, payload));
+                async move {
+                    let Some(future) = future else {
+                        return Flow::Continue;
+                    };
+                    let answer: CallbackAnswer = future.await.into();
+                    if let Err(error) = functions::answer_callback_query(
+                        query_id,
+                        answer.text,
+                        answer.show_alert,
+                        answer.url,
+                        answer.cache_time,
+                        client_id,
+                    )
+                    .await
+                    {
+                        log::warn!("Failed to answer the callback query: {:?}", error);
+                    }
+                    Flow::Stop
+                }
+            })
+            .filter(|update| {
+                matches!(update, Update::NewCallbackQuery(query) if payload::<P>(query).is_some())
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Vote(i64, bool);
+
+    impl CallbackData for Vote {
+        const PREFIX: &'static str = "vote";
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Page(u32);
+
+    impl CallbackData for Page {
+        const PREFIX: &'static str = "page";
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Text(String);
+
+    impl CallbackData for Text {
+        const PREFIX: &'static str = "text";
+    }
+
+    #[test]
+    fn encode_decode() {
+        let data = encode(&Vote(42, true)).unwrap();
+        assert_eq!(data, b"vote:[42,true]");
+        assert_eq!(decode::<Vote>(&data), Some(Vote(42, true)));
+        assert_eq!(decode::<Page>(&data), None);
+    }
+
+    #[test]
+    fn encode_too_long() {
+        assert!(matches!(
+            encode(&Vote(i64::MAX, true)),
+            Ok(data) if data.len() == 31
+        ));
+        assert!(matches!(
+            encode(&Page(u32::MAX)).map(|data| data.len()),
+            Ok(15)
+        ));
+        assert!(matches!(
+            encode(&Text("x".repeat(70))),
+            Err(CallbackDataError::TooLong(77))
+        ));
+        assert_eq!(
+            encode(&Text("x".repeat(70))).unwrap_err().to_string(),
+            "the encoded payload is 77 bytes long, the limit is 64"
+        );
+    }
+
+    #[test]
+    fn build_keyboard() {
+        let ReplyMarkup::InlineKeyboard(keyboard) = InlineKeyboardBuilder::new()
+            .payload("Yes", &Vote(1, true))
+            .payload("No", &Vote(1, false))
+            .row()
+            .row()
+            .url("Site", "https://example.com")
+            .build()
+        else {
+            unreachable!()
+        };
+        assert_eq!(keyboard.rows.len(), 2);
+        assert_eq!(keyboard.rows[0].len(), 2);
+        let InlineKeyboardButtonType::Callback(callback) = &keyboard.rows[0][0].r#type else {
+            unreachable!()
+        };
+        assert_eq!(STANDARD.decode(&callback.data).unwrap(), b"vote:[1,true]");
+    }
+}
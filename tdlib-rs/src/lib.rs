@@ -7,20 +7,46 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod build;
+#[cfg(all(feature = "bots-only-api", feature = "dispatcher"))]
+pub mod commands;
+#[cfg(feature = "connection")]
+pub mod connection;
+#[cfg(feature = "conversation")]
+pub mod conversation;
+#[cfg(feature = "dispatcher")]
+pub mod dispatcher;
+pub mod error;
+#[cfg(feature = "files")]
+pub mod files;
+#[cfg(feature = "formatting")]
+pub mod formatting;
 mod generated;
+#[cfg(feature = "keyboard")]
+pub mod keyboard;
 mod observer;
+#[cfg(feature = "pagination")]
+pub mod pagination;
+#[cfg(feature = "send")]
+pub mod send;
+#[cfg(feature = "state")]
+pub mod state;
 mod tdjson;
 
-pub use generated::{enums, functions, types};
+pub use generated::{enums, functions, types, update_handler};
 
 use enums::Update;
+use futures_channel::mpsc;
 use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 static EXTRA_COUNTER: AtomicU32 = AtomicU32::new(0);
 static OBSERVER: Lazy<observer::Observer> = Lazy::new(observer::Observer::new);
+static UPDATE_OBSERVER: Lazy<observer::UpdateObserver> = Lazy::new(observer::UpdateObserver::new);
 
 /// Create a TdLib client returning its id. Note that to start receiving
 /// updates for a client you need to send at least a request with it first.
@@ -43,8 +69,21 @@ pub fn receive() -> Option<(Update, i32)> {
             }
             None => {
                 let client_id = response["@client_id"].as_i64().unwrap() as i32;
+                // The updates not listed in `TDLIB_RS_ALLOWLIST` are
+                // expected, so they are skipped quietly
+                #[cfg(tdlib_rs_allowlist)]
+                {
+                    let ty = response["@type"].as_str().unwrap_or_default();
+                    if generated::allowlist::UPDATES.binary_search(&ty).is_err() {
+                        log::debug!("Skipped an update not listed in TDLIB_RS_ALLOWLIST: {ty}");
+                        return None;
+                    }
+                }
                 match serde_json::from_value(response) {
                     Ok(update) => {
+                        #[cfg(feature = "connection")]
+                        connection::notify(&update, client_id);
+                        UPDATE_OBSERVER.notify(&update, client_id);
                         return Some((update, client_id));
                     }
                     Err(e) => {
@@ -58,7 +97,26 @@ pub fn receive() -> Option<(Update, i32)> {
     None
 }
 
-pub(crate) async fn send_request(client_id: i32, mut request: Value) -> Value {
+/// Subscribe to the updates of a client that satisfy `filter`. The updates
+/// are still returned by [`receive`], and a copy of them is also sent to the
+/// returned receiver, until it's dropped. Note that [`receive`] must be
+/// called for the updates to be delivered.
+pub fn subscribe<F>(client_id: i32, filter: F) -> mpsc::UnboundedReceiver<Update>
+where
+    F: Fn(&Update) -> bool + Send + Sync + 'static,
+{
+    UPDATE_OBSERVER.subscribe(client_id, Box::new(filter))
+}
+
+/// Send a request to TDLib and wait for its response. The request is the
+/// JSON object of a TDLib function, such as `{"@type": "getMe"}`, and the
+/// response is either the result of the function or an `error` object.
+///
+/// It's used by the generated functions. The code generated ahead of time by
+/// `tdlib-rs-gen` refers to it in the module given with `--root-path`, such
+/// as `crate::send_request`, so a crate checking it in must re-export it
+/// there, for example with `pub use tdlib_rs::send_request;`.
+pub async fn send_request(client_id: i32, mut request: Value) -> Value {
     let extra = EXTRA_COUNTER.fetch_add(1, Ordering::Relaxed);
     request["@extra"] = serde_json::to_value(extra).unwrap();
 
@@ -67,3 +125,74 @@ pub(crate) async fn send_request(client_id: i32, mut request: Value) -> Value {
 
     receiver.await.unwrap()
 }
+
+/// Send a request to TDLib and block the current thread until its response
+/// is received, like [`send_request`].
+#[cfg(feature = "blocking")]
+pub fn send_request_blocking(client_id: i32, mut request: Value) -> Value {
+    let extra = EXTRA_COUNTER.fetch_add(1, Ordering::Relaxed);
+    request["@extra"] = serde_json::to_value(extra).unwrap();
+
+    let receiver = OBSERVER.subscribe(extra);
+    tdjson::send(client_id, request.to_string());
+
+    futures_executor::block_on(receiver).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::AuthorizationState;
+    use types::UpdateAuthorizationState;
+    use update_handler::{UpdateHandler, dispatch};
+
+    #[derive(Default)]
+    struct Recorder {
+        states: Vec<(i32, AuthorizationState)>,
+    }
+
+    impl UpdateHandler for Recorder {
+        async fn on_authorization_state(
+            &mut self,
+            client_id: i32,
+            update: UpdateAuthorizationState,
+        ) {
+            self.states.push((client_id, update.authorization_state));
+        }
+    }
+
+    fn authorization_state(authorization_state: AuthorizationState) -> Update {
+        Update::AuthorizationState(UpdateAuthorizationState {
+            authorization_state,
+        })
+    }
+
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+
+    #[test]
+    fn dispatch_to_stateful_handler() {
+        let mut handler = Recorder::default();
+        let updates = [
+            (
+                authorization_state(AuthorizationState::WaitTdlibParameters),
+                1,
+            ),
+            (authorization_state(AuthorizationState::Ready), 1),
+            (authorization_state(AuthorizationState::Closed), 2),
+        ];
+        for (update, client_id) in updates {
+            futures_executor::block_on(assert_send(dispatch(&mut handler, update, client_id)));
+        }
+
+        assert_eq!(
+            handler.states,
+            [
+                (1, AuthorizationState::WaitTdlibParameters),
+                (1, AuthorizationState::Ready),
+                (2, AuthorizationState::Closed),
+            ]
+        );
+    }
+}
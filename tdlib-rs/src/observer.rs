@@ -7,10 +7,11 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use futures_channel::oneshot;
+use crate::enums::Update;
+use futures_channel::{mpsc, oneshot};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
 
 pub(super) struct Observer {
     requests: RwLock<HashMap<u32, oneshot::Sender<Value>>>,
@@ -43,3 +44,49 @@ impl Observer {
         }
     }
 }
+
+type UpdateFilter = Box<dyn Fn(&Update) -> bool + Send + Sync>;
+
+struct UpdateSubscriber {
+    client_id: i32,
+    filter: UpdateFilter,
+    sender: mpsc::UnboundedSender<Update>,
+}
+
+pub(super) struct UpdateObserver {
+    subscribers: Mutex<Vec<UpdateSubscriber>>,
+}
+
+impl UpdateObserver {
+    pub fn new() -> Self {
+        UpdateObserver {
+            subscribers: Mutex::default(),
+        }
+    }
+
+    pub fn subscribe(
+        &self,
+        client_id: i32,
+        filter: UpdateFilter,
+    ) -> mpsc::UnboundedReceiver<Update> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribers.lock().unwrap().push(UpdateSubscriber {
+            client_id,
+            filter,
+            sender,
+        });
+        receiver
+    }
+
+    pub fn notify(&self, update: &Update, client_id: i32) {
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            if subscriber.sender.is_closed() {
+                return false;
+            }
+            if subscriber.client_id == client_id && (subscriber.filter)(update) {
+                return subscriber.sender.unbounded_send(update.clone()).is_ok();
+            }
+            true
+        });
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The pagination module contains streams over the results of the functions
+//! returning their results in chunks, such as `getChatHistory`.
+//!
+//! Every stream requests the next chunk only when the previous one has been
+//! consumed, and ends when there are no more results or after the first
+//! error. When TDLib answers with a "Too Many Requests: retry after N" error,
+//! the request is sent again after the given number of seconds.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # async fn run(chat_id: i64, client_id: i32) {
+//! use futures_util::StreamExt;
+//! use tdlib_rs::pagination;
+//!
+//! let messages: Vec<_> = pagination::history(chat_id, client_id)
+//!     .take(1000)
+//!     .collect()
+//!     .await;
+//! # }
+//! ```
+use crate::enums::{
+    self, ChatList, MessageSender, MessageTopic, SearchMessagesChatTypeFilter,
+    SearchMessagesFilter, SupergroupMembersFilter,
+};
+use crate::error::{ErrorKind, TdError};
+use crate::functions;
+use crate::types::{ChatJoinRequest, ChatMember, Error, Message};
+use futures_timer::Delay;
+use futures_util::{Stream, StreamExt, stream};
+use std::future::Future;
+use std::time::Duration;
+
+/// A chunk of results, with the cursor to get the next one, if any.
+struct Page<T, C> {
+    items: Vec<T>,
+    next: Option<C>,
+}
+
+/// Build a stream over the results returned by `fetch`, which is called with
+/// the cursor of each chunk, starting from `cursor`.
+fn paginate<T, C, F, Fut>(cursor: C, fetch: F) -> impl Stream<Item = Result<T, Error>>
+where
+    C: Clone,
+    F: FnMut(C) -> Fut,
+    Fut: Future<Output = Result<Page<T, C>, Error>>,
+{
+    stream::unfold((Some(cursor), fetch), |(cursor, mut fetch)| async move {
+        let cursor = cursor?;
+        let (items, next) = loop {
+            match fetch(cursor.clone()).await {
+                Ok(page) => break (page.items.into_iter().map(Ok).collect(), page.next),
+                Err(error) => {
+                    let error = TdError::from(error);
+                    match error.kind() {
+                        ErrorKind::FloodWait { retry_after } => {
+                            Delay::new(Duration::from_secs(*retry_after)).await
+                        }
+                        _ => break (vec![Err(error.into_error())], None),
+                    }
+                }
+            }
+        };
+        Some((stream::iter(items), (next, fetch)))
+    })
+    .flatten()
+}
+
+/// Get a stream over the messages of a chat, from the last one to the first
+/// one.
+///
+/// # Arguments
+/// * `chat_id` - Chat identifier
+/// * `client_id` - The client id to send the requests to
+pub fn history(chat_id: i64, client_id: i32) -> impl Stream<Item = Result<Message, Error>> {
+    paginate(0, move |from_message_id| async move {
+        let enums::Messages::Messages(messages) =
+            functions::get_chat_history(chat_id, from_message_id, 0, 100, false, client_id).await?;
+        let items: Vec<Message> = messages.messages.into_iter().flatten().collect();
+        let next = items.last().map(|message| message.id);
+        Ok(Page { items, next })
+    })
+}
+
+/// Get a stream over the messages found searching in a chat, from the last
+/// one to the first one.
+///
+/// # Arguments
+/// * `chat_id` - Identifier of the chat in which to search messages
+/// * `topic_id` - Pass topic identifier to search messages only in specific topic; pass null to search for messages in all topics
+/// * `query` - Query to search for
+/// * `sender_id` - Identifier of the sender of messages to search for; pass null to search for messages from any sender
+/// * `filter` - Additional filter for messages to search; pass null to search for all messages
+/// * `client_id` - The client id to send the requests to
+pub fn search_chat_messages(
+    chat_id: i64,
+    topic_id: Option<MessageTopic>,
+    query: String,
+    sender_id: Option<MessageSender>,
+    filter: Option<SearchMessagesFilter>,
+    client_id: i32,
+) -> impl Stream<Item = Result<Message, Error>> {
+    paginate(0, move |from_message_id| {
+        let topic_id = topic_id.clone();
+        let query = query.clone();
+        let sender_id = sender_id.clone();
+        let filter = filter.clone();
+        async move {
+            let enums::FoundChatMessages::FoundChatMessages(found) =
+                functions::search_chat_messages(
+                    chat_id,
+                    topic_id,
+                    query,
+                    sender_id,
+                    from_message_id,
+                    0,
+                    100,
+                    filter,
+                    client_id,
+                )
+                .await?;
+            let next = (found.next_from_message_id != 0).then_some(found.next_from_message_id);
+            Ok(Page {
+                items: found.messages,
+                next,
+            })
+        }
+    })
+}
+
+/// Get a stream over the messages found searching in all chats.
+///
+/// # Arguments
+/// * `chat_list` - Chat list in which to search messages; pass null to search in all chats regardless of their chat list
+/// * `query` - Query to search for
+/// * `filter` - Additional filter for messages to search; pass null to search for all messages
+/// * `chat_type_filter` - Additional filter for type of the chat of the searched messages; pass null to search for messages in all chats
+/// * `min_date` - If not 0, the minimum date of the messages to return
+/// * `max_date` - If not 0, the maximum date of the messages to return
+/// * `client_id` - The client id to send the requests to
+pub fn search_messages(
+    chat_list: Option<ChatList>,
+    query: String,
+    filter: Option<SearchMessagesFilter>,
+    chat_type_filter: Option<SearchMessagesChatTypeFilter>,
+    min_date: i32,
+    max_date: i32,
+    client_id: i32,
+) -> impl Stream<Item = Result<Message, Error>> {
+    paginate(String::new(), move |offset| {
+        let chat_list = chat_list.clone();
+        let query = query.clone();
+        let filter = filter.clone();
+        let chat_type_filter = chat_type_filter.clone();
+        async move {
+            let enums::FoundMessages::FoundMessages(found) = functions::search_messages(
+                chat_list,
+                query,
+                offset,
+                100,
+                filter,
+                chat_type_filter,
+                min_date,
+                max_date,
+                client_id,
+            )
+            .await?;
+            let next = (!found.next_offset.is_empty()).then_some(found.next_offset);
+            Ok(Page {
+                items: found.messages,
+                next,
+            })
+        }
+    })
+}
+
+/// Get a stream over the members of a supergroup or channel.
+///
+/// # Arguments
+/// * `supergroup_id` - Identifier of the supergroup or channel
+/// * `filter` - The type of users to return; pass null to use supergroupMembersFilterRecent
+/// * `client_id` - The client id to send the requests to
+pub fn supergroup_members(
+    supergroup_id: i64,
+    filter: Option<SupergroupMembersFilter>,
+    client_id: i32,
+) -> impl Stream<Item = Result<ChatMember, Error>> {
+    paginate(0, move |offset| {
+        let filter = filter.clone();
+        async move {
+            let enums::ChatMembers::ChatMembers(members) =
+                functions::get_supergroup_members(supergroup_id, filter, offset, 200, client_id)
+                    .await?;
+            let offset = offset + members.members.len() as i32;
+            let next =
+                (!members.members.is_empty() && offset < members.total_count).then_some(offset);
+            Ok(Page {
+                items: members.members,
+                next,
+            })
+        }
+    })
+}
+
+/// Get a stream over the pending join requests of a chat.
+///
+/// # Arguments
+/// * `chat_id` - Chat identifier
+/// * `invite_link` - Invite link for which to return join requests. If empty, all join requests will be returned
+/// * `query` - A query to search for in the first names, last names and usernames of the users to return
+/// * `client_id` - The client id to send the requests to
+pub fn chat_join_requests(
+    chat_id: i64,
+    invite_link: String,
+    query: String,
+    client_id: i32,
+) -> impl Stream<Item = Result<ChatJoinRequest, Error>> {
+    paginate(None, move |offset_request| {
+        let invite_link = invite_link.clone();
+        let query = query.clone();
+        async move {
+            let enums::ChatJoinRequests::ChatJoinRequests(requests) =
+                functions::get_chat_join_requests(
+                    chat_id,
+                    invite_link,
+                    query,
+                    offset_request,
+                    100,
+                    client_id,
+                )
+                .await?;
+            let next = requests.requests.last().cloned().map(Some);
+            Ok(Page {
+                items: requests.requests,
+                next,
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_flood_wait_without_runtime() {
+        let mut calls = 0;
+        let stream = paginate(0, |cursor: i32| {
+            calls += 1;
+            let result = match (calls, cursor) {
+                (1, _) => Err(Error {
+                    code: 429,
+                    message: "Too Many Requests: retry after 0".into(),
+                }),
+                (_, 0) => Ok(Page {
+                    items: vec![1, 2],
+                    next: Some(2),
+                }),
+                (_, _) => Ok(Page {
+                    items: vec![3],
+                    next: None,
+                }),
+            };
+            async move { result }
+        });
+
+        let items: Vec<_> = futures_executor::block_on(stream.collect());
+        assert_eq!(items, [Ok(1), Ok(2), Ok(3)]);
+    }
+
+    #[test]
+    fn end_after_error() {
+        let stream = paginate(0, |_: i32| async {
+            Err::<Page<i32, i32>, _>(Error {
+                code: 400,
+                message: "CHAT_NOT_FOUND".into(),
+            })
+        });
+
+        let items: Vec<_> = futures_executor::block_on(stream.collect());
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The send module contains helpers to wait until a message is actually sent.
+//!
+//! Functions such as `sendMessage` return a temporary message, which is
+//! replaced by the final one with `updateMessageSendSucceeded`, or marked as
+//! failed with `updateMessageSendFailed`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::future::Future;
+//! # use tdlib_rs::{enums, types};
+//! # async fn run(
+//! #     chat_id: i64,
+//! #     client_id: i32,
+//! #     request: impl Future<Output = Result<enums::Message, types::Error>>,
+//! # ) {
+//! use tdlib_rs::send;
+//!
+//! // `request` is a call to `functions::send_message`, not yet awaited
+//! let message = send::confirm(chat_id, client_id, request).await.unwrap();
+//! println!("Sent message {}", message.id);
+//! # }
+//! ```
+use crate::enums::{self, AuthorizationState, Update};
+use crate::error::TdError;
+use crate::subscribe;
+use crate::types::{Error, Message};
+use futures_timer::Delay;
+use futures_util::StreamExt;
+use futures_util::future::{self, Either};
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+use std::time::Duration;
+
+/// An error returned when a message can't be sent.
+#[derive(Debug)]
+pub enum SendError {
+    /// TDLib returned an error to the request.
+    Request(Error),
+    /// The message was not sent. It contains the failed message and the
+    /// reason of the failure.
+    Failed {
+        /// The failed message.
+        message: Box<Message>,
+        /// The reason of the failure.
+        error: TdError,
+    },
+    /// The client was closed before the message was sent.
+    Closed,
+    /// The message was not sent within the timeout.
+    TimedOut,
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(error) => write!(
+                f,
+                "the request failed with TDLib error {}: {}",
+                error.code, error.message
+            ),
+            Self::Failed { message, error } => {
+                write!(f, "the message {} was not sent: {}", message.id, error)
+            }
+            Self::Closed => write!(f, "the client was closed before the message was sent"),
+            Self::TimedOut => write!(f, "the message was not sent within the timeout"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Failed { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for SendError {
+    fn from(error: Error) -> Self {
+        Self::Request(error)
+    }
+}
+
+/// Wait until the message returned by `request` is sent, returning the final
+/// message. `request` must be a not yet awaited call of a function returning
+/// a message of the chat, such as `sendMessage`, so that no update can be
+/// missed.
+///
+/// The wait ends with [`SendError::Closed`] if the client starts closing
+/// before the message is sent, since TDLib sends no more updates about it.
+///
+/// # Arguments
+/// * `chat_id` - Identifier of the chat of the message
+/// * `client_id` - Identifier of the client sending the message
+/// * `request` - The request sending the message
+pub async fn confirm<F>(chat_id: i64, client_id: i32, request: F) -> Result<Message, SendError>
+where
+    F: Future<Output = Result<enums::Message, Error>>,
+{
+    let mut updates = subscribe(client_id, move |update| match update {
+        Update::MessageSendSucceeded(update) => update.message.chat_id == chat_id,
+        Update::MessageSendFailed(update) => update.message.chat_id == chat_id,
+        Update::AuthorizationState(update) => matches!(
+            update.authorization_state,
+            AuthorizationState::Closing | AuthorizationState::Closed
+        ),
+        // Unreachable if no other update is generated, with `TDLIB_RS_ALLOWLIST`
+        #[allow(unreachable_patterns)]
+        _ => false,
+    });
+
+    let enums::Message::Message(message) = request.await?;
+    if message.sending_state.is_none() {
+        return Ok(message);
+    }
+
+    while let Some(update) = updates.next().await {
+        match update {
+            Update::MessageSendSucceeded(update) if update.old_message_id == message.id => {
+                return Ok(update.message);
+            }
+            Update::MessageSendFailed(update) if update.old_message_id == message.id => {
+                return Err(SendError::Failed {
+                    message: Box::new(update.message),
+                    error: update.error.into(),
+                });
+            }
+            Update::AuthorizationState(_) => break,
+            _ => {}
+        }
+    }
+    Err(SendError::Closed)
+}
+
+/// Wait until the message returned by `request` is sent, like [`confirm`],
+/// returning [`SendError::TimedOut`] if it's not sent within `timeout`.
+/// Unlike `tokio::time::timeout`, it doesn't require a Tokio runtime.
+pub async fn confirm_timeout<F>(
+    chat_id: i64,
+    client_id: i32,
+    request: F,
+    timeout: Duration,
+) -> Result<Message, SendError>
+where
+    F: Future<Output = Result<enums::Message, Error>>,
+{
+    let confirm = pin!(confirm(chat_id, client_id, request));
+    match future::select(confirm, Delay::new(timeout)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(SendError::TimedOut),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UPDATE_OBSERVER;
+    use crate::enums::{MessageContent, MessageSender, MessageSendingState};
+    use crate::types::{
+        FormattedText, MessageSenderUser, MessageSendingStatePending, MessageText,
+        UpdateAuthorizationState, UpdateMessageSendFailed, UpdateMessageSendSucceeded,
+    };
+
+    const CHAT_ID: i64 = 10;
+
+    fn message(id: i64, pending: bool) -> Message {
+        Message {
+            id,
+            sender_id: MessageSender::User(MessageSenderUser { user_id: 1 }),
+            chat_id: CHAT_ID,
+            sending_state: pending.then_some(MessageSendingState::Pending(
+                MessageSendingStatePending { sending_id: 0 },
+            )),
+            scheduling_state: None,
+            is_outgoing: true,
+            is_pinned: false,
+            is_from_offline: false,
+            can_be_saved: true,
+            has_timestamped_media: false,
+            is_channel_post: false,
+            is_paid_star_suggested_post: false,
+            is_paid_ton_suggested_post: false,
+            contains_unread_mention: false,
+            date: 0,
+            edit_date: 0,
+            forward_info: None,
+            import_info: None,
+            interaction_info: None,
+            unread_reactions: Vec::new(),
+            fact_check: None,
+            suggested_post_info: None,
+            reply_to: None,
+            topic_id: None,
+            self_destruct_type: None,
+            self_destruct_in: 0.0,
+            auto_delete_in: 0.0,
+            via_bot_user_id: 0,
+            sender_business_bot_user_id: 0,
+            sender_boost_count: 0,
+            paid_message_star_count: 0,
+            author_signature: String::new(),
+            media_album_id: 0,
+            effect_id: 0,
+            restriction_info: None,
+            summary_language_code: String::new(),
+            content: MessageContent::MessageText(MessageText {
+                text: FormattedText::default(),
+                link_preview: None,
+                link_preview_options: None,
+            }),
+            reply_markup: None,
+        }
+    }
+
+    fn succeeded(old_message_id: i64, id: i64) -> Update {
+        Update::MessageSendSucceeded(UpdateMessageSendSucceeded {
+            message: message(id, false),
+            old_message_id,
+        })
+    }
+
+    fn failed(old_message_id: i64) -> Update {
+        Update::MessageSendFailed(UpdateMessageSendFailed {
+            message: message(old_message_id, false),
+            old_message_id,
+            error: Error {
+                code: 400,
+                message: "MESSAGE_TOO_LONG".into(),
+            },
+        })
+    }
+
+    /// Returns a request sending a pending message with the identifier
+    /// `id`, which receives `updates` before its response.
+    async fn request(
+        client_id: i32,
+        id: i64,
+        updates: Vec<Update>,
+    ) -> Result<enums::Message, Error> {
+        for update in &updates {
+            UPDATE_OBSERVER.notify(update, client_id);
+        }
+        Ok(enums::Message::Message(message(id, true)))
+    }
+
+    #[test]
+    fn confirm_sent_message() {
+        let client_id = -20;
+        let request = async { Ok(enums::Message::Message(message(1, false))) };
+        let message = futures_executor::block_on(confirm(CHAT_ID, client_id, request)).unwrap();
+        assert_eq!(message.id, 1);
+    }
+
+    #[test]
+    fn confirm_succeeded_by_old_message_id() {
+        let client_id = -21;
+        let updates = vec![succeeded(2, 20), succeeded(1, 10)];
+        let request = request(client_id, 1, updates);
+        let message = futures_executor::block_on(confirm(CHAT_ID, client_id, request)).unwrap();
+        assert_eq!(message.id, 10);
+    }
+
+    #[test]
+    fn confirm_failed_by_old_message_id() {
+        let client_id = -22;
+        let updates = vec![failed(2), succeeded(3, 30), failed(1)];
+        let request = request(client_id, 1, updates);
+        match futures_executor::block_on(confirm(CHAT_ID, client_id, request)) {
+            Err(SendError::Failed { message, error }) => {
+                assert_eq!(message.id, 1);
+                assert_eq!(error.error().message, "MESSAGE_TOO_LONG");
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn confirm_closed() {
+        let client_id = -23;
+        let closing = Update::AuthorizationState(UpdateAuthorizationState {
+            authorization_state: AuthorizationState::Closing,
+        });
+        let request = request(client_id, 1, vec![closing]);
+        let result = futures_executor::block_on(confirm(CHAT_ID, client_id, request));
+        assert!(matches!(result, Err(SendError::Closed)));
+    }
+
+    #[test]
+    fn confirm_timed_out() {
+        let client_id = -24;
+        let request = request(client_id, 1, vec![succeeded(2, 20)]);
+        let timeout = Duration::from_millis(10);
+        let result =
+            futures_executor::block_on(confirm_timeout(CHAT_ID, client_id, request, timeout));
+        assert!(matches!(result, Err(SendError::TimedOut)));
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::enums::{ChatList, Update};
+use crate::functions;
+use crate::types::{ChatPosition, Error};
+
+/// A chat in a chat list of a [`ChatListModel`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChatListEntry {
+    /// Chat identifier.
+    pub chat_id: i64,
+    /// The order of the chat in the chat list.
+    pub order: i64,
+    /// True, if the chat is pinned in the chat list.
+    pub is_pinned: bool,
+}
+
+/// A change of a chat list of a [`ChatListModel`]. The indexes refer to the
+/// sorted view of the chat list before the change for the removed chats, and
+/// after the change for the inserted and updated ones, so the events can be
+/// replayed in order on a copy of the view.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChatListEvent {
+    /// A chat was added to the chat list at `index`.
+    Inserted {
+        list: ChatList,
+        chat_id: i64,
+        index: usize,
+    },
+    /// A chat was removed from the chat list, where it was at `index`.
+    Removed {
+        list: ChatList,
+        chat_id: i64,
+        index: usize,
+    },
+    /// A chat moved from the index `from` to the index `to` of the chat
+    /// list, possibly changing whether it's pinned.
+    Moved {
+        list: ChatList,
+        chat_id: i64,
+        from: usize,
+        to: usize,
+    },
+    /// A chat changed its order or whether it's pinned, keeping its `index`
+    /// in the chat list.
+    Updated {
+        list: ChatList,
+        chat_id: i64,
+        index: usize,
+    },
+}
+
+/// An ordered view of the chat lists (main, archive and folders), kept up to
+/// date from the `updateNewChat`, `updateChatPosition`,
+/// `updateChatLastMessage` and `updateChatDraftMessage` updates.
+///
+/// Chats are sorted by the pair (order, chat identifier) in descending order,
+/// so pinned chats always come first.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tdlib_rs::enums::ChatList;
+/// use tdlib_rs::state::ChatListModel;
+///
+/// let mut model = ChatListModel::new();
+/// while let Some((update, _client_id)) = tdlib_rs::receive() {
+///     for event in model.apply(&update) {
+///         println!("{event:?}");
+///     }
+/// }
+///
+/// for entry in model.chats(&ChatList::Main) {
+///     println!("{} {}", entry.chat_id, entry.is_pinned);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ChatListModel {
+    lists: Vec<(ChatList, Vec<ChatListEntry>)>,
+}
+
+impl ChatListModel {
+    /// Create an empty model.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Iterate over the chat lists containing at least one chat.
+    pub fn lists(&self) -> impl Iterator<Item = &ChatList> {
+        self.lists
+            .iter()
+            .filter(|(_, entries)| !entries.is_empty())
+            .map(|(list, _)| list)
+    }
+
+    /// Iterate over the chats of a chat list, in order.
+    pub fn chats(&self, list: &ChatList) -> impl Iterator<Item = &ChatListEntry> {
+        self.entries(list).iter()
+    }
+
+    /// Iterate over the pinned chats of a chat list, in order.
+    pub fn pinned_chats(&self, list: &ChatList) -> impl Iterator<Item = &ChatListEntry> {
+        self.chats(list).filter(|entry| entry.is_pinned)
+    }
+
+    /// Get the number of chats in a chat list.
+    pub fn len(&self, list: &ChatList) -> usize {
+        self.entries(list).len()
+    }
+
+    /// Returns `true` if the chat list contains no chats.
+    pub fn is_empty(&self, list: &ChatList) -> bool {
+        self.entries(list).is_empty()
+    }
+
+    /// Get the index of a chat in the sorted view of a chat list.
+    pub fn index_of(&self, list: &ChatList, chat_id: i64) -> Option<usize> {
+        self.entries(list)
+            .iter()
+            .position(|entry| entry.chat_id == chat_id)
+    }
+
+    /// Apply an update to the model, returning the resulting changes of the
+    /// chat lists.
+    pub fn apply(&mut self, update: &Update) -> Vec<ChatListEvent> {
+        let mut events = Vec::new();
+        match update {
+            Update::NewChat(update) => {
+                self.set_positions(update.chat.id, &update.chat.positions, &mut events);
+            }
+            Update::ChatPosition(update) => {
+                self.set_position(update.chat_id, &update.position, &mut events);
+            }
+            Update::ChatLastMessage(update) => {
+                self.set_positions(update.chat_id, &update.positions, &mut events);
+            }
+            Update::ChatDraftMessage(update) => {
+                self.set_positions(update.chat_id, &update.positions, &mut events);
+            }
+            _ => (),
+        }
+        events
+    }
+
+    fn entries(&self, list: &ChatList) -> &[ChatListEntry] {
+        self.lists
+            .iter()
+            .find(|(l, _)| l == list)
+            .map(|(_, entries)| entries.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Replace all the positions of a chat, removing it from the chat lists
+    /// not contained in `positions`.
+    fn set_positions(
+        &mut self,
+        chat_id: i64,
+        positions: &[ChatPosition],
+        events: &mut Vec<ChatListEvent>,
+    ) {
+        for (list, entries) in self.lists.iter_mut() {
+            if positions.iter().any(|p| p.list == *list) {
+                continue;
+            }
+            if let Some(index) = entries.iter().position(|e| e.chat_id == chat_id) {
+                entries.remove(index);
+                events.push(ChatListEvent::Removed {
+                    list: list.clone(),
+                    chat_id,
+                    index,
+                });
+            }
+        }
+
+        for position in positions {
+            self.set_position(chat_id, position, events);
+        }
+    }
+
+    /// Set the position of a chat in a single chat list, removing it if the
+    /// new order is 0.
+    fn set_position(
+        &mut self,
+        chat_id: i64,
+        position: &ChatPosition,
+        events: &mut Vec<ChatListEvent>,
+    ) {
+        let entries = match self.lists.iter().position(|(l, _)| *l == position.list) {
+            Some(i) => &mut self.lists[i].1,
+            None => {
+                if position.order == 0 {
+                    return;
+                }
+                self.lists.push((position.list.clone(), Vec::new()));
+                &mut self.lists.last_mut().unwrap().1
+            }
+        };
+
+        let from = entries.iter().position(|e| e.chat_id == chat_id);
+        let old_entry = from.map(|from| entries.remove(from));
+
+        if position.order == 0 {
+            if let Some(index) = from {
+                events.push(ChatListEvent::Removed {
+                    list: position.list.clone(),
+                    chat_id,
+                    index,
+                });
+            }
+            return;
+        }
+
+        let to = entries.partition_point(|e| (e.order, e.chat_id) > (position.order, chat_id));
+        let entry = ChatListEntry {
+            chat_id,
+            order: position.order,
+            is_pinned: position.is_pinned,
+        };
+        entries.insert(to, entry);
+
+        match from {
+            None => events.push(ChatListEvent::Inserted {
+                list: position.list.clone(),
+                chat_id,
+                index: to,
+            }),
+            Some(from) if from != to => events.push(ChatListEvent::Moved {
+                list: position.list.clone(),
+                chat_id,
+                from,
+                to,
+            }),
+            Some(index) if old_entry != Some(entry) => events.push(ChatListEvent::Updated {
+                list: position.list.clone(),
+                chat_id,
+                index,
+            }),
+            Some(_) => (),
+        }
+    }
+}
+
+/// Call `loadChats` until all the chats of `chat_list` have been loaded, that
+/// is until TDLib returns a 404 error. The loaded chats are sent through
+/// updates, which can be applied to a [`ChatListModel`].
+pub async fn load_chats(chat_list: ChatList, limit: i32, client_id: i32) -> Result<(), Error> {
+    loop {
+        match functions::load_chats(Some(chat_list.clone()), limit, client_id).await {
+            Ok(()) => (),
+            Err(error) if error.code == 404 => return Ok(()),
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::{chat, chat_position, new_chat, position};
+
+    fn chat_ids(model: &ChatListModel) -> Vec<i64> {
+        model.chats(&ChatList::Main).map(|e| e.chat_id).collect()
+    }
+
+    fn main_chat(id: i64, order: i64) -> Update {
+        let mut chat = chat(id);
+        chat.positions = vec![position(ChatList::Main, order, false)];
+        new_chat(chat)
+    }
+
+    #[test]
+    fn insert_chats_in_order() {
+        let mut model = ChatListModel::new();
+        model.apply(&main_chat(1, 10));
+        model.apply(&main_chat(2, 30));
+        let events = model.apply(&main_chat(3, 20));
+
+        assert_eq!(chat_ids(&model), [2, 3, 1]);
+        assert_eq!(
+            events,
+            [ChatListEvent::Inserted {
+                list: ChatList::Main,
+                chat_id: 3,
+                index: 1,
+            }]
+        );
+
+        // Chats with the same order are sorted by identifier
+        model.apply(&main_chat(4, 20));
+        assert_eq!(chat_ids(&model), [2, 4, 3, 1]);
+    }
+
+    #[test]
+    fn move_chats() {
+        let mut model = ChatListModel::new();
+        model.apply(&main_chat(1, 10));
+        model.apply(&main_chat(2, 20));
+        model.apply(&main_chat(3, 30));
+
+        let events = model.apply(&chat_position(1, position(ChatList::Main, 40, false)));
+        assert_eq!(chat_ids(&model), [1, 3, 2]);
+        assert_eq!(
+            events,
+            [ChatListEvent::Moved {
+                list: ChatList::Main,
+                chat_id: 1,
+                from: 2,
+                to: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn update_pinned_chats() {
+        let mut model = ChatListModel::new();
+        model.apply(&main_chat(1, 10));
+        model.apply(&main_chat(2, 20));
+
+        let events = model.apply(&chat_position(2, position(ChatList::Main, 20, true)));
+        assert_eq!(chat_ids(&model), [2, 1]);
+        assert_eq!(
+            events,
+            [ChatListEvent::Updated {
+                list: ChatList::Main,
+                chat_id: 2,
+                index: 0,
+            }]
+        );
+        assert_eq!(
+            model
+                .pinned_chats(&ChatList::Main)
+                .map(|e| e.chat_id)
+                .collect::<Vec<_>>(),
+            [2]
+        );
+
+        // Nothing changed
+        let events = model.apply(&chat_position(2, position(ChatList::Main, 20, true)));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn remove_chats() {
+        let mut model = ChatListModel::new();
+        model.apply(&main_chat(1, 10));
+        model.apply(&main_chat(2, 20));
+        model.apply(&main_chat(3, 30));
+
+        let events = model.apply(&chat_position(2, position(ChatList::Main, 0, false)));
+        assert_eq!(chat_ids(&model), [3, 1]);
+        assert_eq!(
+            events,
+            [ChatListEvent::Removed {
+                list: ChatList::Main,
+                chat_id: 2,
+                index: 1,
+            }]
+        );
+
+        // Chats missing from the positions of a chat are removed from the list
+        let mut chat = chat(3);
+        chat.positions = vec![position(ChatList::Archive, 30, false)];
+        let events = model.apply(&new_chat(chat));
+        assert_eq!(chat_ids(&model), [1]);
+        assert_eq!(model.len(&ChatList::Archive), 1);
+        assert_eq!(
+            events,
+            [
+                ChatListEvent::Removed {
+                    list: ChatList::Main,
+                    chat_id: 3,
+                    index: 0,
+                },
+                ChatListEvent::Inserted {
+                    list: ChatList::Archive,
+                    chat_id: 3,
+                    index: 0,
+                },
+            ]
+        );
+    }
+}
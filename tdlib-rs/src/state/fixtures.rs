@@ -0,0 +1,135 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Objects used by the tests of the state module, with all the fields set to
+//! empty values except their identifier.
+use crate::enums::{
+    ChatAvailableReactions, ChatList, ChatMemberStatus, ChatType, OptionValue, Update, UserStatus,
+    UserType,
+};
+use crate::types::{
+    BasicGroup, Chat, ChatAvailableReactionsSome, ChatMemberStatusMember, ChatPosition,
+    ChatTypePrivate, UpdateChatPosition, UpdateNewChat, UpdateOption, UpdateUser, User,
+};
+
+pub(super) fn user(id: i64) -> User {
+    User {
+        id,
+        first_name: String::new(),
+        last_name: String::new(),
+        usernames: None,
+        phone_number: String::new(),
+        status: UserStatus::Empty,
+        profile_photo: None,
+        accent_color_id: 0,
+        background_custom_emoji_id: 0,
+        upgraded_gift_colors: None,
+        profile_accent_color_id: 0,
+        profile_background_custom_emoji_id: 0,
+        emoji_status: None,
+        is_contact: false,
+        is_mutual_contact: false,
+        is_close_friend: false,
+        verification_status: None,
+        is_premium: false,
+        is_support: false,
+        restriction_info: None,
+        active_story_state: None,
+        restricts_new_chats: false,
+        paid_message_star_count: 0,
+        have_access: true,
+        r#type: UserType::Regular,
+        language_code: String::new(),
+        added_to_attachment_menu: false,
+    }
+}
+
+pub(super) fn chat(id: i64) -> Chat {
+    Chat {
+        id,
+        r#type: ChatType::Private(ChatTypePrivate { user_id: id }),
+        title: String::new(),
+        photo: None,
+        accent_color_id: 0,
+        background_custom_emoji_id: 0,
+        upgraded_gift_colors: None,
+        profile_accent_color_id: 0,
+        profile_background_custom_emoji_id: 0,
+        permissions: Default::default(),
+        last_message: None,
+        positions: Vec::new(),
+        chat_lists: Vec::new(),
+        message_sender_id: None,
+        block_list: None,
+        has_protected_content: false,
+        is_translatable: false,
+        is_marked_as_unread: false,
+        view_as_topics: false,
+        has_scheduled_messages: false,
+        can_be_deleted_only_for_self: false,
+        can_be_deleted_for_all_users: false,
+        can_be_reported: false,
+        default_disable_notification: false,
+        unread_count: 0,
+        last_read_inbox_message_id: 0,
+        last_read_outbox_message_id: 0,
+        unread_mention_count: 0,
+        unread_reaction_count: 0,
+        notification_settings: Default::default(),
+        available_reactions: ChatAvailableReactions::Some(ChatAvailableReactionsSome::default()),
+        message_auto_delete_time: 0,
+        emoji_status: None,
+        background: None,
+        theme: None,
+        action_bar: None,
+        business_bot_manage_bar: None,
+        video_chat: Default::default(),
+        pending_join_requests: None,
+        reply_markup_message_id: 0,
+        draft_message: None,
+        client_data: String::new(),
+    }
+}
+
+pub(super) fn basic_group(id: i64) -> BasicGroup {
+    BasicGroup {
+        id,
+        member_count: 0,
+        status: ChatMemberStatus::Member(ChatMemberStatusMember::default()),
+        is_active: true,
+        upgraded_to_supergroup_id: 0,
+    }
+}
+
+pub(super) fn position(list: ChatList, order: i64, is_pinned: bool) -> ChatPosition {
+    ChatPosition {
+        list,
+        order,
+        is_pinned,
+        source: None,
+    }
+}
+
+pub(super) fn new_user(id: i64) -> Update {
+    Update::User(UpdateUser { user: user(id) })
+}
+
+pub(super) fn new_chat(chat: Chat) -> Update {
+    Update::NewChat(UpdateNewChat { chat })
+}
+
+pub(super) fn chat_position(chat_id: i64, position: ChatPosition) -> Update {
+    Update::ChatPosition(UpdateChatPosition { chat_id, position })
+}
+
+pub(super) fn option(name: &str, value: OptionValue) -> Update {
+    Update::Option(UpdateOption {
+        name: name.into(),
+        value,
+    })
+}
@@ -0,0 +1,23 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The state module contains the structures used to keep a local copy of the
+//! TDLib state, built from the updates received with [`receive`](crate::receive).
+//!
+//! TDLib sends the full objects only once (for example with `updateNewChat`)
+//! and then only the fields that change (for example with `updateChatTitle`),
+//! so applications must apply every update to their own copy of the objects.
+mod chat_list;
+#[cfg(test)]
+mod fixtures;
+mod options;
+mod store;
+
+pub use chat_list::{ChatListEntry, ChatListEvent, ChatListModel, load_chats};
+pub use options::Options;
+pub use store::{Change, Store};
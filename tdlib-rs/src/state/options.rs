@@ -0,0 +1,304 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::enums::{OptionValue, Update};
+use crate::functions;
+use crate::types::{Error, OptionValueBoolean, OptionValueInteger, OptionValueString};
+use futures_channel::mpsc;
+use std::collections::HashMap;
+
+/// A store of the TDLib options, kept up to date by applying the
+/// `updateOption` updates received from TDLib.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tdlib_rs::state::Options;
+///
+/// let mut options = Options::new();
+/// while let Some((update, _client_id)) = tdlib_rs::receive() {
+///     if options.apply(&update).is_some() {
+///         println!("my_id: {:?}", options.my_id());
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Options {
+    values: HashMap<String, OptionValue>,
+    subscribers: Vec<mpsc::UnboundedSender<String>>,
+}
+
+macro_rules! accessors {
+    ($($(#[$attr:meta])* $name:ident: $getter:ident -> $ty:ty;)*) => {
+        $(
+            $(#[$attr])*
+            pub fn $name(&self) -> Option<$ty> {
+                self.$getter(stringify!($name))
+            }
+        )*
+    };
+}
+
+impl Options {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the value of an option, if known and not empty.
+    pub fn get(&self, name: &str) -> Option<&OptionValue> {
+        self.values.get(name)
+    }
+
+    /// Get the value of a boolean option.
+    pub fn boolean(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            OptionValue::Boolean(value) => Some(value.value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of an integer option.
+    pub fn integer(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            OptionValue::Integer(value) => Some(value.value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a string option.
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            OptionValue::String(value) => Some(&value.value),
+            _ => None,
+        }
+    }
+
+    accessors! {
+        /// Get the identifier of the current user.
+        my_id: integer -> i64;
+        /// Get the TDLib version.
+        version: string -> &str;
+        /// Get the commit hash of the TDLib build.
+        commit_hash: string -> &str;
+        /// Get the current Unix timestamp, according to the Telegram servers.
+        unix_time: integer -> i64;
+        /// Get the current UTC time offset of the user, in seconds.
+        utc_time_offset: integer -> i64;
+        /// Get the point in time when the current user was authorized.
+        authorization_date: integer -> i64;
+        /// Get whether the current user has a Telegram Premium subscription.
+        is_premium: boolean -> bool;
+        /// Get whether Telegram Premium is available to the current user.
+        is_premium_available: boolean -> bool;
+        /// Get whether the current user is online.
+        online: boolean -> bool;
+        /// Get the identifier of the current language pack.
+        language_pack_id: string -> &str;
+        /// Get the maximum length of a message text.
+        message_text_length_max: integer -> i64;
+        /// Get the maximum length of a message caption.
+        message_caption_length_max: integer -> i64;
+        /// Get the maximum number of members in a basic group.
+        basic_group_size_max: integer -> i64;
+        /// Get the maximum number of members in a supergroup.
+        supergroup_size_max: integer -> i64;
+        /// Get the maximum number of messages that can be forwarded at once.
+        forwarded_message_count_max: integer -> i64;
+        /// Get the maximum number of pinned chats in the main chat list.
+        pinned_chat_count_max: integer -> i64;
+        /// Get the maximum number of chat folders.
+        chat_folder_count_max: integer -> i64;
+        /// Get the maximum size of a file to be uploaded, in bytes.
+        upload_size_max: integer -> i64;
+    }
+
+    /// Subscribe to the changes of the store. The name of every option
+    /// changed by [`Options::apply`] or by a setter is also sent to the
+    /// returned receiver, until it's dropped.
+    pub fn subscribe(&mut self) -> mpsc::UnboundedReceiver<String> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Apply an update to the store, returning the name of the changed
+    /// option. Updates other than `updateOption` are ignored.
+    pub fn apply<'a>(&mut self, update: &'a Update) -> Option<&'a str> {
+        let Update::Option(update) = update else {
+            return None;
+        };
+        self.store(&update.name, update.value.clone());
+        Some(&update.name)
+    }
+
+    /// Get the value of an option from TDLib with `getOption`, storing it.
+    pub async fn fetch(&mut self, name: &str, client_id: i32) -> Result<(), Error> {
+        let value = functions::get_option(name.into(), client_id).await?;
+        self.store(name, value);
+        Ok(())
+    }
+
+    /// Set the value of an option with `setOption`, storing it on success.
+    pub async fn set(
+        &mut self,
+        name: &str,
+        value: OptionValue,
+        client_id: i32,
+    ) -> Result<(), Error> {
+        functions::set_option(name.into(), Some(value.clone()), client_id).await?;
+        self.store(name, value);
+        Ok(())
+    }
+
+    /// Set the value of a boolean option with `setOption`.
+    pub async fn set_boolean(
+        &mut self,
+        name: &str,
+        value: bool,
+        client_id: i32,
+    ) -> Result<(), Error> {
+        let value = OptionValue::Boolean(OptionValueBoolean { value });
+        self.set(name, value, client_id).await
+    }
+
+    /// Set the value of an integer option with `setOption`.
+    pub async fn set_integer(
+        &mut self,
+        name: &str,
+        value: i64,
+        client_id: i32,
+    ) -> Result<(), Error> {
+        let value = OptionValue::Integer(OptionValueInteger { value });
+        self.set(name, value, client_id).await
+    }
+
+    /// Set the value of a string option with `setOption`.
+    pub async fn set_string(
+        &mut self,
+        name: &str,
+        value: &str,
+        client_id: i32,
+    ) -> Result<(), Error> {
+        let value = OptionValue::String(OptionValueString {
+            value: value.into(),
+        });
+        self.set(name, value, client_id).await
+    }
+
+    /// Reset an option to its default value with `setOption`.
+    pub async fn reset(&mut self, name: &str, client_id: i32) -> Result<(), Error> {
+        functions::set_option(name.into(), None, client_id).await?;
+        self.store(name, OptionValue::Empty);
+        Ok(())
+    }
+
+    /// Set whether the current user is online.
+    pub async fn set_online(&mut self, online: bool, client_id: i32) -> Result<(), Error> {
+        self.set_boolean("online", online, client_id).await
+    }
+
+    /// Set the identifier of the current language pack.
+    pub async fn set_language_pack_id(&mut self, id: &str, client_id: i32) -> Result<(), Error> {
+        self.set_string("language_pack_id", id, client_id).await
+    }
+
+    /// Set the UTC time offset of the user, in seconds.
+    pub async fn set_utc_time_offset(&mut self, offset: i64, client_id: i32) -> Result<(), Error> {
+        self.set_integer("utc_time_offset", offset, client_id).await
+    }
+
+    fn store(&mut self, name: &str, value: OptionValue) {
+        match value {
+            OptionValue::Empty => self.values.remove(name),
+            value => self.values.insert(name.into(), value),
+        };
+        self.subscribers
+            .retain(|subscriber| subscriber.unbounded_send(name.into()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::{new_user, option};
+
+    fn integer(value: i64) -> OptionValue {
+        OptionValue::Integer(OptionValueInteger { value })
+    }
+
+    fn string(value: &str) -> OptionValue {
+        OptionValue::String(OptionValueString {
+            value: value.into(),
+        })
+    }
+
+    fn boolean(value: bool) -> OptionValue {
+        OptionValue::Boolean(OptionValueBoolean { value })
+    }
+
+    #[test]
+    fn apply_option_values() {
+        let mut options = Options::new();
+        assert_eq!(options.apply(&option("my_id", integer(42))), Some("my_id"));
+        assert_eq!(
+            options.apply(&option("version", string("1.8.61"))),
+            Some("version")
+        );
+        assert_eq!(
+            options.apply(&option("online", boolean(true))),
+            Some("online")
+        );
+        assert_eq!(options.apply(&new_user(1)), None);
+
+        assert_eq!(options.integer("my_id"), Some(42));
+        assert_eq!(options.string("version"), Some("1.8.61"));
+        assert_eq!(options.boolean("online"), Some(true));
+        // The typed getters return `None` for the values of another kind
+        assert_eq!(options.boolean("my_id"), None);
+        assert_eq!(options.integer("version"), None);
+        assert_eq!(options.string("online"), None);
+
+        // Empty values remove the option
+        options.apply(&option("my_id", OptionValue::Empty));
+        assert_eq!(options.get("my_id"), None);
+    }
+
+    #[test]
+    fn accessors() {
+        let mut options = Options::new();
+        assert_eq!(options.my_id(), None);
+
+        options.apply(&option("my_id", integer(42)));
+        options.apply(&option("version", string("1.8.61")));
+        options.apply(&option("is_premium", boolean(true)));
+        assert_eq!(options.my_id(), Some(42));
+        assert_eq!(options.version(), Some("1.8.61"));
+        assert_eq!(options.is_premium(), Some(true));
+
+        // An accessor of the wrong kind doesn't match the value
+        options.apply(&option("online", integer(1)));
+        assert_eq!(options.online(), None);
+    }
+
+    #[test]
+    fn notify_subscribers() {
+        let mut options = Options::new();
+        let mut changes = options.subscribe();
+        let dropped = options.subscribe();
+        drop(dropped);
+
+        options.apply(&option("my_id", integer(42)));
+        options.apply(&option("my_id", OptionValue::Empty));
+        options.apply(&new_user(1));
+
+        assert_eq!(changes.try_recv().unwrap(), "my_id");
+        assert_eq!(changes.try_recv().unwrap(), "my_id");
+        assert!(changes.try_recv().is_err());
+        assert_eq!(options.subscribers.len(), 1);
+    }
+}
@@ -0,0 +1,469 @@
+// Copyright 2024 - developers of the `tgt` and `tdlib-rs` projects.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::enums::Update;
+use crate::types::{BasicGroup, Chat, ChatPosition, Supergroup, User};
+use futures_channel::mpsc;
+use std::collections::HashMap;
+
+/// The entity modified by an update applied to a [`Store`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// The user with the given identifier was added or modified.
+    User(i64),
+    /// The chat with the given identifier was added or modified.
+    Chat(i64),
+    /// The supergroup with the given identifier was added or modified.
+    Supergroup(i64),
+    /// The basic group with the given identifier was added or modified.
+    BasicGroup(i64),
+}
+
+/// An in-memory store of users, chats, supergroups and basic groups, kept up
+/// to date by applying the updates received from TDLib.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tdlib_rs::state::Store;
+///
+/// let mut store = Store::new();
+/// while let Some((update, _client_id)) = tdlib_rs::receive() {
+///     if let Some(change) = store.apply(&update) {
+///         println!("{change:?}");
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Store {
+    users: HashMap<i64, User>,
+    chats: HashMap<i64, Chat>,
+    supergroups: HashMap<i64, Supergroup>,
+    basic_groups: HashMap<i64, BasicGroup>,
+    subscribers: Vec<mpsc::UnboundedSender<Change>>,
+}
+
+impl Store {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the user with the given identifier, if known.
+    pub fn user(&self, user_id: i64) -> Option<&User> {
+        self.users.get(&user_id)
+    }
+
+    /// Get the chat with the given identifier, if known.
+    pub fn chat(&self, chat_id: i64) -> Option<&Chat> {
+        self.chats.get(&chat_id)
+    }
+
+    /// Get the supergroup with the given identifier, if known.
+    pub fn supergroup(&self, supergroup_id: i64) -> Option<&Supergroup> {
+        self.supergroups.get(&supergroup_id)
+    }
+
+    /// Get the basic group with the given identifier, if known.
+    pub fn basic_group(&self, basic_group_id: i64) -> Option<&BasicGroup> {
+        self.basic_groups.get(&basic_group_id)
+    }
+
+    /// Iterate over all the known users, in arbitrary order.
+    pub fn users(&self) -> impl Iterator<Item = &User> {
+        self.users.values()
+    }
+
+    /// Iterate over all the known chats, in arbitrary order.
+    pub fn chats(&self) -> impl Iterator<Item = &Chat> {
+        self.chats.values()
+    }
+
+    /// Iterate over all the known supergroups, in arbitrary order.
+    pub fn supergroups(&self) -> impl Iterator<Item = &Supergroup> {
+        self.supergroups.values()
+    }
+
+    /// Iterate over all the known basic groups, in arbitrary order.
+    pub fn basic_groups(&self) -> impl Iterator<Item = &BasicGroup> {
+        self.basic_groups.values()
+    }
+
+    /// Subscribe to the changes of the store. Every change returned by
+    /// [`Store::apply`] is also sent to the returned receiver, until it's
+    /// dropped.
+    pub fn subscribe(&mut self) -> mpsc::UnboundedReceiver<Change> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Apply an update to the store, returning the modified entity. Updates
+    /// not related to the stored entities, or related to entities that are
+    /// not known yet, are ignored.
+    pub fn apply(&mut self, update: &Update) -> Option<Change> {
+        let change = match update {
+            Update::User(update) => {
+                self.users.insert(update.user.id, update.user.clone());
+                Change::User(update.user.id)
+            }
+            Update::UserStatus(update) => {
+                let user = self.users.get_mut(&update.user_id)?;
+                user.status = update.status.clone();
+                Change::User(update.user_id)
+            }
+            Update::Supergroup(update) => {
+                let supergroup = &update.supergroup;
+                self.supergroups.insert(supergroup.id, supergroup.clone());
+                Change::Supergroup(supergroup.id)
+            }
+            Update::BasicGroup(update) => {
+                let basic_group = &update.basic_group;
+                self.basic_groups
+                    .insert(basic_group.id, basic_group.clone());
+                Change::BasicGroup(basic_group.id)
+            }
+            Update::NewChat(update) => {
+                self.chats.insert(update.chat.id, update.chat.clone());
+                Change::Chat(update.chat.id)
+            }
+            update => {
+                let chat_id = apply_chat_update(&mut self.chats, update)?;
+                Change::Chat(chat_id)
+            }
+        };
+
+        self.subscribers
+            .retain(|subscriber| subscriber.unbounded_send(change).is_ok());
+
+        Some(change)
+    }
+}
+
+/// Apply a field-level chat update, returning the identifier of the modified
+/// chat.
+fn apply_chat_update(chats: &mut HashMap<i64, Chat>, update: &Update) -> Option<i64> {
+    let chat_id = match update {
+        Update::ChatTitle(update) => {
+            chats.get_mut(&update.chat_id)?.title = update.title.clone();
+            update.chat_id
+        }
+        Update::ChatPhoto(update) => {
+            chats.get_mut(&update.chat_id)?.photo = update.photo.clone();
+            update.chat_id
+        }
+        Update::ChatAccentColors(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.accent_color_id = update.accent_color_id;
+            chat.background_custom_emoji_id = update.background_custom_emoji_id;
+            chat.upgraded_gift_colors = update.upgraded_gift_colors.clone();
+            chat.profile_accent_color_id = update.profile_accent_color_id;
+            chat.profile_background_custom_emoji_id = update.profile_background_custom_emoji_id;
+            update.chat_id
+        }
+        Update::ChatPermissions(update) => {
+            chats.get_mut(&update.chat_id)?.permissions = update.permissions.clone();
+            update.chat_id
+        }
+        Update::ChatLastMessage(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.last_message = update.last_message.clone();
+            chat.positions = update.positions.clone();
+            update.chat_id
+        }
+        Update::ChatPosition(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            set_chat_position(&mut chat.positions, &update.position);
+            update.chat_id
+        }
+        Update::ChatAddedToList(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            if !chat.chat_lists.contains(&update.chat_list) {
+                chat.chat_lists.push(update.chat_list.clone());
+            }
+            update.chat_id
+        }
+        Update::ChatRemovedFromList(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.chat_lists.retain(|list| *list != update.chat_list);
+            update.chat_id
+        }
+        Update::ChatReadInbox(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.last_read_inbox_message_id = update.last_read_inbox_message_id;
+            chat.unread_count = update.unread_count;
+            update.chat_id
+        }
+        Update::ChatReadOutbox(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.last_read_outbox_message_id = update.last_read_outbox_message_id;
+            update.chat_id
+        }
+        Update::ChatActionBar(update) => {
+            chats.get_mut(&update.chat_id)?.action_bar = update.action_bar.clone();
+            update.chat_id
+        }
+        Update::ChatBusinessBotManageBar(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.business_bot_manage_bar = update.business_bot_manage_bar.clone();
+            update.chat_id
+        }
+        Update::ChatAvailableReactions(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.available_reactions = update.available_reactions.clone();
+            update.chat_id
+        }
+        Update::ChatDraftMessage(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.draft_message = update.draft_message.clone();
+            chat.positions = update.positions.clone();
+            update.chat_id
+        }
+        Update::ChatEmojiStatus(update) => {
+            chats.get_mut(&update.chat_id)?.emoji_status = update.emoji_status.clone();
+            update.chat_id
+        }
+        Update::ChatMessageSender(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.message_sender_id = update.message_sender_id.clone();
+            update.chat_id
+        }
+        Update::ChatMessageAutoDeleteTime(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.message_auto_delete_time = update.message_auto_delete_time;
+            update.chat_id
+        }
+        Update::ChatNotificationSettings(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.notification_settings = update.notification_settings.clone();
+            update.chat_id
+        }
+        Update::ChatPendingJoinRequests(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.pending_join_requests = update.pending_join_requests.clone();
+            update.chat_id
+        }
+        Update::ChatReplyMarkup(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.reply_markup_message_id = update.reply_markup_message_id;
+            update.chat_id
+        }
+        Update::ChatBackground(update) => {
+            chats.get_mut(&update.chat_id)?.background = update.background.clone();
+            update.chat_id
+        }
+        Update::ChatTheme(update) => {
+            chats.get_mut(&update.chat_id)?.theme = update.theme.clone();
+            update.chat_id
+        }
+        Update::ChatUnreadMentionCount(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.unread_mention_count = update.unread_mention_count;
+            update.chat_id
+        }
+        Update::ChatUnreadReactionCount(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.unread_reaction_count = update.unread_reaction_count;
+            update.chat_id
+        }
+        Update::MessageMentionRead(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.unread_mention_count = update.unread_mention_count;
+            update.chat_id
+        }
+        Update::MessageUnreadReactions(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.unread_reaction_count = update.unread_reaction_count;
+            update.chat_id
+        }
+        Update::ChatVideoChat(update) => {
+            chats.get_mut(&update.chat_id)?.video_chat = update.video_chat.clone();
+            update.chat_id
+        }
+        Update::ChatDefaultDisableNotification(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.default_disable_notification = update.default_disable_notification;
+            update.chat_id
+        }
+        Update::ChatHasProtectedContent(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.has_protected_content = update.has_protected_content;
+            update.chat_id
+        }
+        Update::ChatIsTranslatable(update) => {
+            chats.get_mut(&update.chat_id)?.is_translatable = update.is_translatable;
+            update.chat_id
+        }
+        Update::ChatIsMarkedAsUnread(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.is_marked_as_unread = update.is_marked_as_unread;
+            update.chat_id
+        }
+        Update::ChatViewAsTopics(update) => {
+            chats.get_mut(&update.chat_id)?.view_as_topics = update.view_as_topics;
+            update.chat_id
+        }
+        Update::ChatBlockList(update) => {
+            chats.get_mut(&update.chat_id)?.block_list = update.block_list.clone();
+            update.chat_id
+        }
+        Update::ChatHasScheduledMessages(update) => {
+            let chat = chats.get_mut(&update.chat_id)?;
+            chat.has_scheduled_messages = update.has_scheduled_messages;
+            update.chat_id
+        }
+        _ => return None,
+    };
+
+    Some(chat_id)
+}
+
+/// Replace the position of a chat in the chat list of `position`, removing it
+/// if its order is 0.
+fn set_chat_position(positions: &mut Vec<ChatPosition>, position: &ChatPosition) {
+    positions.retain(|p| p.list != position.list);
+    if position.order != 0 {
+        positions.push(position.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{ChatList, UserStatus};
+    use crate::state::fixtures::{self, chat, chat_position, new_chat, new_user, position};
+    use crate::types::{
+        UpdateBasicGroup, UpdateChatAddedToList, UpdateChatReadInbox, UpdateChatRemovedFromList,
+        UpdateChatTitle, UpdateMessageMentionRead, UpdateUserStatus, UserStatusOnline,
+    };
+
+    #[test]
+    fn apply_user_updates() {
+        let mut store = Store::new();
+        assert_eq!(store.apply(&new_user(1)), Some(Change::User(1)));
+
+        let status = UserStatus::Online(UserStatusOnline { expires: 10 });
+        let update = Update::UserStatus(UpdateUserStatus {
+            user_id: 1,
+            status: status.clone(),
+        });
+        assert_eq!(store.apply(&update), Some(Change::User(1)));
+        assert_eq!(store.user(1).unwrap().status, status);
+        assert_eq!(store.users().count(), 1);
+    }
+
+    #[test]
+    fn ignore_updates_of_unknown_entities() {
+        let mut store = Store::new();
+        let update = Update::UserStatus(UpdateUserStatus {
+            user_id: 1,
+            status: UserStatus::Empty,
+        });
+        assert_eq!(store.apply(&update), None);
+
+        let update = Update::ChatTitle(UpdateChatTitle {
+            chat_id: 1,
+            title: "Title".into(),
+        });
+        assert_eq!(store.apply(&update), None);
+        assert!(store.user(1).is_none());
+        assert!(store.chat(1).is_none());
+    }
+
+    #[test]
+    fn apply_chat_updates() {
+        let mut store = Store::new();
+        assert_eq!(store.apply(&new_chat(chat(1))), Some(Change::Chat(1)));
+
+        let updates = [
+            Update::ChatTitle(UpdateChatTitle {
+                chat_id: 1,
+                title: "Title".into(),
+            }),
+            Update::ChatReadInbox(UpdateChatReadInbox {
+                chat_id: 1,
+                last_read_inbox_message_id: 5,
+                unread_count: 3,
+            }),
+            Update::MessageMentionRead(UpdateMessageMentionRead {
+                chat_id: 1,
+                message_id: 5,
+                unread_mention_count: 2,
+            }),
+            Update::ChatAddedToList(UpdateChatAddedToList {
+                chat_id: 1,
+                chat_list: ChatList::Archive,
+            }),
+        ];
+        for update in &updates {
+            assert_eq!(store.apply(update), Some(Change::Chat(1)));
+        }
+
+        let chat = store.chat(1).unwrap();
+        assert_eq!(chat.title, "Title");
+        assert_eq!(chat.last_read_inbox_message_id, 5);
+        assert_eq!(chat.unread_count, 3);
+        assert_eq!(chat.unread_mention_count, 2);
+        assert_eq!(chat.chat_lists, [ChatList::Archive]);
+
+        let update = Update::ChatRemovedFromList(UpdateChatRemovedFromList {
+            chat_id: 1,
+            chat_list: ChatList::Archive,
+        });
+        assert_eq!(store.apply(&update), Some(Change::Chat(1)));
+        assert!(store.chat(1).unwrap().chat_lists.is_empty());
+    }
+
+    #[test]
+    fn apply_chat_position_updates() {
+        let mut store = Store::new();
+        store.apply(&new_chat(chat(1)));
+
+        store.apply(&chat_position(1, position(ChatList::Main, 10, false)));
+        store.apply(&chat_position(1, position(ChatList::Archive, 20, false)));
+        store.apply(&chat_position(1, position(ChatList::Main, 30, true)));
+        let positions = &store.chat(1).unwrap().positions;
+        assert_eq!(positions.len(), 2);
+        assert!(positions.contains(&position(ChatList::Main, 30, true)));
+
+        store.apply(&chat_position(1, position(ChatList::Archive, 0, false)));
+        assert_eq!(
+            store.chat(1).unwrap().positions,
+            [position(ChatList::Main, 30, true)]
+        );
+    }
+
+    #[test]
+    fn apply_basic_group_updates() {
+        let mut store = Store::new();
+        let mut basic_group = fixtures::basic_group(1);
+        basic_group.member_count = 3;
+        let update = Update::BasicGroup(UpdateBasicGroup { basic_group });
+        assert_eq!(store.apply(&update), Some(Change::BasicGroup(1)));
+        assert_eq!(store.basic_group(1).unwrap().member_count, 3);
+    }
+
+    #[test]
+    fn notify_subscribers() {
+        let mut store = Store::new();
+        let mut changes = store.subscribe();
+        let dropped = store.subscribe();
+        drop(dropped);
+
+        store.apply(&new_user(1));
+        store.apply(&new_chat(chat(2)));
+        store.apply(&Update::ChatTitle(UpdateChatTitle {
+            chat_id: 3,
+            title: String::new(),
+        }));
+
+        assert_eq!(changes.try_recv().unwrap(), Change::User(1));
+        assert_eq!(changes.try_recv().unwrap(), Change::Chat(2));
+        assert!(changes.try_recv().is_err());
+        assert_eq!(store.subscribers.len(), 1);
+    }
+}